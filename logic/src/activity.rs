@@ -0,0 +1,309 @@
+//! Board-wide activity feed.
+//!
+//! Every notable write appends an [`Activity`] to a bounded log. Its ID comes
+//! from the [`IdAllocator`](crate::alloc::IdAllocator) like any other, so
+//! executors writing concurrently never pick the same one, and entries are
+//! ordered by timestamp, then ID. The log may run [`ACTIVITY_LOG_SLACK`] entries
+//! past [`ACTIVITY_LOG_CAPACITY`], at which point the oldest are dropped in one
+//! go, so writes don't pay for an eviction each.
+
+use std::cmp::Reverse;
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use calimero_storage::collections::UnorderedMap;
+
+use crate::assignment::AssignmentId;
+use crate::bid::BidId;
+use crate::bounty::BountyId;
+use crate::env;
+use crate::error::{bail, storage};
+use crate::message::{MessageId, MessageTarget};
+use crate::paging::{self, ResumptionToken};
+use crate::schema;
+use crate::types::id;
+use crate::user::UserId;
+use crate::AppState;
+
+id::define!(pub ActivityId<8, 12> as "act");
+
+pub const ACTIVITY_LOG_CAPACITY: usize = 1_000;
+
+pub const ACTIVITY_LOG_SLACK: usize = 100;
+
+const DEFAULT_ACTIVITY_PAGE: usize = 20;
+
+/// Replies can nest, but not indefinitely.
+pub const MAX_THREAD_DEPTH: usize = 32;
+
+const TOKEN_TAG: u8 = b'a';
+
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum ActivityKind {
+    BountyCreated,
+    BountyEdited,
+    UserInvited {
+        user: UserId,
+    },
+    BidPlaced {
+        bid: BidId,
+    },
+    AssignmentOffered {
+        assignment: AssignmentId,
+        assignee: UserId,
+    },
+    AssignmentAccepted {
+        assignment: AssignmentId,
+    },
+    AssignmentDeclined {
+        assignment: AssignmentId,
+    },
+    Commented {
+        message: MessageId,
+        target: MessageTarget,
+    },
+    BidCountered {
+        bid: BidId,
+        recipient: UserId,
+    },
+    CounterOfferAccepted {
+        bid: BidId,
+        recipient: UserId,
+    },
+}
+
+schema::versioned! {
+    #[derive(Debug)]
+    @version(1)
+    pub struct Activity {
+        pub actor: UserId,
+        pub bounty: Option<BountyId>,
+        pub kind: ActivityKind,
+        pub timestamp: u64,
+    }
+}
+
+/// Where an activity sits in the feed, which is ordered by this.
+pub type ActivityKey = (u64, ActivityId);
+
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct ActivityLog {
+    entries: UnorderedMap<ActivityId, Activity>,
+    /// Key of the latest entry each user recorded, kept per actor so
+    /// concurrent writers don't overwrite each other's.
+    latest: UnorderedMap<UserId, ActivityKey>,
+}
+
+impl ActivityLog {
+    /// Retained entries, in no particular order.
+    pub fn entries(&self) -> app::Result<impl Iterator<Item = (ActivityId, Activity)> + '_> {
+        Ok(self.entries.entries().map_err(storage)?)
+    }
+
+    pub fn contains(&self, id: &ActivityId) -> app::Result<bool> {
+        Ok(self.entries.contains(id).map_err(storage)?)
+    }
+
+    /// Key of the most recent entry, if any.
+    pub fn latest(&self) -> app::Result<Option<ActivityKey>> {
+        Ok(self
+            .latest
+            .entries()
+            .map_err(storage)?
+            .map(|(_, key)| key)
+            .max())
+    }
+
+    fn push(&mut self, id: ActivityId, activity: Activity) -> app::Result<()> {
+        let actor = activity.actor;
+        let key = (activity.timestamp, id);
+
+        let _ignored = self.entries.insert(id, activity).map_err(storage)?;
+
+        let stale = self
+            .latest
+            .get(&actor)
+            .map_err(storage)?
+            .is_some_and(|latest| latest >= key);
+
+        if !stale {
+            let _ignored = self.latest.insert(actor, key).map_err(storage)?;
+        }
+
+        let len = self.entries.len().map_err(storage)?;
+
+        if len <= ACTIVITY_LOG_CAPACITY + ACTIVITY_LOG_SLACK {
+            return Ok(());
+        }
+
+        let mut keys = self
+            .entries()?
+            .map(|(id, activity)| (activity.timestamp, id))
+            .collect::<Vec<_>>();
+
+        keys.sort_unstable();
+
+        for (_, id) in keys.into_iter().take(len - ACTIVITY_LOG_CAPACITY) {
+            let _ignored = self.entries.remove(&id).map_err(storage)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_token((timestamp, id): ActivityKey) -> ResumptionToken {
+    let mut token = [0; 17];
+
+    token[0] = TOKEN_TAG;
+    token[1..9].copy_from_slice(&timestamp.to_be_bytes());
+    token[9..].copy_from_slice(&id[..]);
+
+    ResumptionToken::new(token)
+}
+
+fn decode_token(token: ResumptionToken) -> app::Result<ActivityKey> {
+    let [TOKEN_TAG, rest @ ..] = **token else {
+        bail!(paging::Error::InvalidResumptionToken(token));
+    };
+
+    let (timestamp, id) = rest.split_at(8);
+
+    let timestamp = u64::from_be_bytes(timestamp.try_into().expect("8 bytes"));
+    let id = ActivityId::new(id.try_into().expect("8 bytes"));
+
+    Ok((timestamp, id))
+}
+
+impl AppState {
+    /// Appends to the feed as the current user, notifying whoever it concerns.
+    pub fn record_activity(
+        &mut self,
+        bounty: Option<BountyId>,
+        kind: ActivityKind,
+    ) -> app::Result<()> {
+        let activity = Activity {
+            actor: self.current_user(),
+            bounty,
+            kind,
+            timestamp: env::time_now(),
+        };
+
+        let id = self
+            .ids
+            .next(|id: &ActivityId| self.activity.entries.contains(id))?;
+
+        self.notify(id, &activity)?;
+
+        self.activity.push(id, activity)
+    }
+
+    /// Finds the bounty a message thread ultimately hangs off, if any.
+    pub fn thread_bounty(&self, target: &MessageTarget) -> app::Result<Option<BountyId>> {
+        let mut target = match target {
+            MessageTarget::Message(id) => *id,
+            MessageTarget::Bounty(id) => return Ok(Some(*id)),
+            MessageTarget::Bid(id) => {
+                return Ok(self.bids.get(id).map_err(storage)?.map(|bid| bid.bounty))
+            }
+            MessageTarget::Assignment(id) => {
+                return Ok(self.assignments.get(id).map_err(storage)?.map(|a| a.bounty))
+            }
+        };
+
+        for _ in 0..MAX_THREAD_DEPTH {
+            let Some(message) = self.messages.get(&target).map_err(storage)? else {
+                return Ok(None);
+            };
+
+            match message.target {
+                MessageTarget::Message(parent) => target = parent,
+                other => return self.thread_bounty(&other),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct ActivityView {
+    pub id: ActivityId,
+    pub actor: UserId,
+    pub bounty: Option<BountyId>,
+    pub kind: ActivityKind,
+    pub timestamp: u64,
+}
+
+#[app::logic]
+impl AppState {
+    /// Lists activity newest first. Pass the returned token back in to get
+    /// the next page, with the same filters.
+    pub fn get_activity(
+        &self,
+        user: Option<UserId>,
+        bounty: Option<BountyId>,
+        resume: Option<ResumptionToken>,
+        length: Option<usize>,
+    ) -> app::Result<(Vec<ActivityView>, Option<ResumptionToken>)> {
+        let before = resume.map(decode_token).transpose()?;
+
+        let mut entries = self
+            .activity
+            .entries()?
+            .filter(|(id, activity)| {
+                let key = (activity.timestamp, *id);
+
+                !before.is_some_and(|before| key >= before)
+                    && !user.is_some_and(|user| activity.actor != user)
+                    && !(bounty.is_some() && activity.bounty != bounty)
+            })
+            .collect::<Vec<_>>();
+
+        entries.sort_by_key(|(id, activity)| Reverse((activity.timestamp, *id)));
+
+        let length = length.unwrap_or(DEFAULT_ACTIVITY_PAGE);
+
+        let resume = length
+            .checked_sub(1)
+            .and_then(|last| entries.get(last))
+            .filter(|_| entries.len() > length)
+            .map(|(id, activity)| encode_token((activity.timestamp, *id)));
+
+        let views = entries
+            .into_iter()
+            .take(length)
+            .map(|(id, activity)| ActivityView {
+                id,
+                actor: activity.actor,
+                bounty: activity.bounty,
+                kind: activity.kind,
+                timestamp: activity.timestamp,
+            })
+            .collect();
+
+        Ok((views, resume))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_roundtrip() {
+        let key = (42, ActivityId::new(*b"activity"));
+
+        assert_eq!(decode_token(encode_token(key)).unwrap(), key);
+    }
+
+    #[test]
+    fn foreign_tokens_are_rejected() {
+        assert!(decode_token(ResumptionToken::new([7; 17])).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use calimero_sdk::app;
+use calimero_sdk::serde::Serialize;
+use thiserror::Error;
+
+use crate::error::{bail, storage, ErrorCode};
+use crate::user::UserId;
+use crate::AppState;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("user is not an admin: {0}")]
+    NotAnAdmin(UserId),
+    #[error("cannot remove the last admin")]
+    LastAdmin,
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::NotAnAdmin(_) => 6001,
+            Error::LastAdmin => 6002,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::NotAnAdmin(id) => Some(id.to_string()),
+            Error::LastAdmin => None,
+        }
+    }
+}
+
+impl AppState {
+    pub fn is_admin(&self, user_id: &UserId) -> app::Result<bool> {
+        Ok(self.admins.contains(user_id).map_err(storage)?)
+    }
+
+    pub fn ensure_admin(&self, user_id: &UserId) -> app::Result<()> {
+        if !self.is_admin(user_id)? {
+            bail!(Error::NotAnAdmin(*user_id));
+        }
+
+        Ok(())
+    }
+}
+
+#[app::logic]
+impl AppState {
+    pub fn add_admin(&mut self, user_id: UserId) -> app::Result<()> {
+        self.ensure_admin(&self.current_user())?;
+
+        let _ignored = self.admins.insert(user_id).map_err(storage)?;
+
+        Ok(())
+    }
+
+    pub fn remove_admin(&mut self, user_id: UserId) -> app::Result<()> {
+        self.ensure_admin(&self.current_user())?;
+
+        if self.admins.len().map_err(storage)? <= 1
+            && self.admins.contains(&user_id).map_err(storage)?
+        {
+            bail!(Error::LastAdmin);
+        }
+
+        let _ignored = self.admins.remove(&user_id).map_err(storage)?;
+
+        Ok(())
+    }
+
+    pub fn list_admins(&self) -> app::Result<Vec<UserId>> {
+        Ok(self.admins.iter().map_err(storage)?.collect())
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use calimero_storage::collections::UnorderedMap;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::env;
+use crate::error::{bail, storage, ErrorCode};
+use crate::types::id::{IdExt, Prefixed};
+use crate::utils::{unique, IntoResult};
+use crate::AppState;
+
+#[derive(Default, Clone, Copy, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum IdStrategy {
+    /// Random bytes, retried against storage on collision.
+    #[default]
+    Random,
+    /// A tag hashed from the ID prefix and the executor, followed by a
+    /// per-executor counter. An executor never repeats itself, so nothing is
+    /// checked against storage. IDs without a prefix stay random.
+    Sequential,
+}
+
+/// Trailing bytes of a sequential ID holding the counter.
+const COUNTER_LENGTH: usize = 4;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("ran out of sequential IDs for `{0}`")]
+    SequenceExhausted(String),
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::SequenceExhausted(_) => 9005,
+        }
+    }
+}
+
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct IdAllocator {
+    pub strategy: IdStrategy,
+    counters: UnorderedMap<String, u64>,
+}
+
+impl IdAllocator {
+    pub fn next<T, R, const N: usize, const M: usize>(
+        &mut self,
+        exists: impl Fn(&T) -> R,
+    ) -> app::Result<T>
+    where
+        T: IdExt<N, M> + From<[u8; N]> + Prefixed,
+        R: IntoResult<bool>,
+        R::Error: fmt::Display,
+    {
+        match (self.strategy, T::PREFIX) {
+            (IdStrategy::Sequential, Some(prefix)) if N > COUNTER_LENGTH => self.sequential(prefix),
+            _ => unique(|| T::random(), exists),
+        }
+    }
+
+    /// Counters of the sequential strategy, keyed by prefix and executor.
+    pub fn counters(&self) -> app::Result<BTreeMap<String, u64>> {
+        Ok(self.counters.entries().map_err(storage)?.collect())
+    }
+
+    /// Moves a counter forward to `value`, never back, so IDs imported from
+    /// elsewhere aren't handed out again.
+    pub fn advance_counter(&mut self, key: String, value: u64) -> app::Result<()> {
+        let current = self
+            .counters
+            .get(&key)
+            .map_err(storage)?
+            .unwrap_or_default();
+
+        if value > current {
+            let _ignored = self.counters.insert(key, value).map_err(storage)?;
+        }
+
+        Ok(())
+    }
+
+    fn sequential<T, const N: usize>(&mut self, prefix: &str) -> app::Result<T>
+    where
+        T: From<[u8; N]>,
+    {
+        let executor = env::executor_id();
+
+        let key = format!("{prefix}:{}", bs58::encode(executor).into_string());
+
+        let counter = self
+            .counters
+            .get(&key)
+            .map_err(storage)?
+            .unwrap_or_default();
+
+        let counter = counter.saturating_add(1);
+
+        let Ok(sequence) = u32::try_from(counter) else {
+            bail!(Error::SequenceExhausted(prefix.to_owned()));
+        };
+
+        let tag = Sha256::new()
+            .chain_update(prefix)
+            .chain_update(executor)
+            .finalize();
+
+        let mut bytes = [0; N];
+
+        let (head, tail) = bytes.split_at_mut(N - COUNTER_LENGTH);
+
+        head.copy_from_slice(&tag[..head.len()]);
+        tail.copy_from_slice(&sequence.to_be_bytes());
+
+        let _ignored = self.counters.insert(key, counter).map_err(storage)?;
+
+        Ok(T::from(bytes))
+    }
+}
+
+#[app::logic]
+impl AppState {
+    pub fn get_id_strategy(&self) -> IdStrategy {
+        self.ids.strategy
+    }
+
+    pub fn set_id_strategy(&mut self, strategy: IdStrategy) -> app::Result<()> {
+        self.ensure_admin(&self.current_user())?;
+
+        self.ids.strategy = strategy;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::bounty::BountyId;
+    use crate::testing::{user, Harness};
+
+    #[test]
+    fn sequential_ids_are_distinct() {
+        let mut harness = Harness::new(user(1));
+
+        harness
+            .as_user(user(1))
+            .set_id_strategy(IdStrategy::Sequential)
+            .unwrap();
+
+        let state = harness.as_user(user(2));
+
+        let ids = (0..100)
+            .map(|_| state.ids.next(|_: &BountyId| false))
+            .collect::<app::Result<BTreeSet<_>>>()
+            .unwrap();
+
+        assert_eq!(ids.len(), 100);
+    }
+
+    #[test]
+    fn sequential_ids_are_not_probed() {
+        let mut harness = Harness::new(user(1));
+
+        let state = harness.as_user(user(1));
+
+        state.set_id_strategy(IdStrategy::Sequential).unwrap();
+
+        let probes = Cell::new(0);
+
+        let _id = state
+            .ids
+            .next(|_: &BountyId| {
+                probes.set(probes.get() + 1);
+
+                true
+            })
+            .unwrap();
+
+        assert_eq!(probes.get(), 0);
+    }
+
+    #[test]
+    fn only_admins_can_change_the_strategy() {
+        let mut harness = Harness::new(user(1));
+
+        let result = harness
+            .as_user(user(2))
+            .set_id_strategy(IdStrategy::Sequential);
+
+        assert!(result.is_err());
+    }
+}
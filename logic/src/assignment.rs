@@ -1,40 +1,578 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use calimero_sdk::app;
 use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
 use calimero_storage::collections::{UnorderedMap, UnorderedSet};
+use thiserror::Error;
 
-use crate::bid::BidId;
+use crate::activity::ActivityKind;
+use crate::bid::{validate_bid_brief, validate_bid_expiry, validate_bid_reward, BidId};
 use crate::bounty::BountyId;
-use crate::message::MessageId;
+use crate::config::AppConfig;
+use crate::env;
+use crate::error::{bail, storage, ErrorCode};
+use crate::event::Event;
+use crate::link::{canonical_link, LinkView};
+use crate::message::{MessageId, MessageTarget};
+use crate::paging::paginate;
+use crate::schema;
+use crate::stats::Change;
+use crate::text;
 use crate::types::id;
 use crate::user::UserId;
+use crate::AppState;
+
+id::define!(pub AssignmentId<8, 12> as "asg");
+
+schema::versioned! {
+    #[derive(Debug)]
+    @version(1)
+    pub struct Assignment {
+        pub assignee: UserId,
+        pub bounty: BountyId,
 
-id::define!(pub AssignmentId<8, 12>);
+        pub message: MessageId,
+        pub bid: Option<BidId>,
+        pub links: UnorderedSet<String>,
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
+        pub status: AssignmentStatus,
+        pub expiry: Option<u64>,
+        pub reward: UnorderedMap<String, u128>,
+        pub duration: Option<u64>,
+
+        pub assigned_at: Option<u64>,
+        pub accepted_at: Option<u64>,
+        pub completed_at: Option<u64>,
+        pub abandoned_at: Option<u64>,
+    }
+}
+
+#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
-pub struct Assignment {
-    pub assignee: UserId,
+#[serde(crate = "calimero_sdk::serde")]
+pub enum AssignmentStatus {
+    Received,
+    InProgress,
+    Completed,
+    Abandoned { reason: Option<String> },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum AssignmentStatusFilter {
+    Received,
+    InProgress,
+    Completed,
+    Abandoned,
+}
+
+impl AssignmentStatusFilter {
+    pub fn against(&self, status: &AssignmentStatus) -> bool {
+        matches!(
+            (self, status),
+            (Self::Received, AssignmentStatus::Received)
+                | (Self::InProgress, AssignmentStatus::InProgress)
+                | (Self::Completed, AssignmentStatus::Completed)
+                | (Self::Abandoned, AssignmentStatus::Abandoned { .. })
+        )
+    }
+}
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("assignment not found: {0}")]
+    AssignmentNotFound(AssignmentId),
+    #[error("only the assignee of {0} can do this")]
+    NotAssignee(AssignmentId),
+    #[error("assignment is not awaiting acceptance: {0}")]
+    AssignmentNotPending(AssignmentId),
+    #[error("assignment is not in progress: {0}")]
+    AssignmentNotInProgress(AssignmentId),
+    #[error("deliverable link is too long ({got} > {max}): {link}")]
+    DeliverableLinkTooLong {
+        link: String,
+        max: usize,
+        got: usize,
+    },
+    #[error("too many deliverable links ({got} > {max})")]
+    DeliverableLinksLimit { max: usize, got: usize },
+    #[error("no such deliverable link on {assignment}: {link}")]
+    DeliverableLinkNotFound {
+        assignment: AssignmentId,
+        link: String,
+    },
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::AssignmentNotFound(_) => 4001,
+            Error::NotAssignee(_) => 4002,
+            Error::AssignmentNotPending(_) => 4003,
+            Error::AssignmentNotInProgress(_) => 4004,
+            Error::DeliverableLinkTooLong { .. } => 4005,
+            Error::DeliverableLinksLimit { .. } => 4006,
+            Error::DeliverableLinkNotFound { .. } => 4007,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::AssignmentNotFound(id)
+            | Error::NotAssignee(id)
+            | Error::AssignmentNotPending(id)
+            | Error::AssignmentNotInProgress(id)
+            | Error::DeliverableLinkNotFound { assignment: id, .. } => Some(id.to_string()),
+            Error::DeliverableLinkTooLong { .. } | Error::DeliverableLinksLimit { .. } => None,
+        }
+    }
+}
+
+impl AppState {
+    pub fn internal_get_assignment(&self, assignment_id: AssignmentId) -> app::Result<Assignment> {
+        let Some(assignment) = self.assignments.get(&assignment_id).map_err(storage)? else {
+            bail!(Error::AssignmentNotFound(assignment_id));
+        };
+
+        Ok(assignment)
+    }
+
+    fn internal_get_pending_assignment_as_assignee(
+        &self,
+        assignment_id: AssignmentId,
+    ) -> app::Result<Assignment> {
+        let assignment = self.internal_get_assignment(assignment_id)?;
+
+        if assignment.assignee != self.current_user() {
+            bail!(Error::NotAssignee(assignment_id));
+        }
+
+        if !matches!(assignment.status, AssignmentStatus::Received) {
+            bail!(Error::AssignmentNotPending(assignment_id));
+        }
+
+        Ok(assignment)
+    }
+
+    fn internal_get_active_assignment_as_assignee(
+        &self,
+        assignment_id: AssignmentId,
+    ) -> app::Result<Assignment> {
+        let assignment = self.internal_get_assignment(assignment_id)?;
+
+        if assignment.assignee != self.current_user() {
+            bail!(Error::NotAssignee(assignment_id));
+        }
+
+        if !matches!(assignment.status, AssignmentStatus::InProgress) {
+            bail!(Error::AssignmentNotInProgress(assignment_id));
+        }
+
+        Ok(assignment)
+    }
+}
+
+fn validate_deliverable_link(config: &AppConfig, link: &str) -> app::Result<String> {
+    let link = canonical_link(link)?;
+
+    let got = text::measure(&link, config.length_policy);
+
+    if got > config.max_deliverable_link_length {
+        bail!(Error::DeliverableLinkTooLong {
+            link,
+            max: config.max_deliverable_link_length,
+            got,
+        });
+    }
+
+    Ok(link)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct DirectAssignmentRequest {
     pub bounty: BountyId,
+    pub assignee: UserId,
+    pub brief: String,
+    pub expiry: Option<u64>,
+    pub reward: BTreeMap<String, u128>,
+    pub duration: Option<u64>,
+}
+
+#[app::logic]
+impl AppState {
+    pub fn assign_directly(
+        &mut self,
+        request: DirectAssignmentRequest,
+    ) -> app::Result<AssignmentId> {
+        let author_id = self.current_user();
+
+        let mut author = self.get_registered_user(&author_id)?;
+
+        let mut bounty = self.internal_get_open_bounty_as_author(request.bounty)?;
+
+        self.ensure_registered_user(&request.assignee)?;
+
+        let brief = validate_bid_brief(&self.config, &request.brief)?;
+        validate_bid_reward(&self.config, request.reward.len())?;
+        validate_bid_expiry(&self.config, request.expiry)?;
+
+        let assignment_id = self
+            .ids
+            .next(|id: &AssignmentId| self.assignments.contains(id))?;
+
+        let message_id = self.internal_post_message(
+            author_id,
+            &mut author,
+            MessageTarget::Assignment(assignment_id),
+            brief,
+        )?;
+
+        let assignment = Assignment {
+            assignee: request.assignee,
+            bounty: request.bounty,
+
+            message: message_id,
+            bid: None,
+            links: UnorderedSet::new(),
+
+            status: AssignmentStatus::Received,
+            expiry: request.expiry,
+            reward: request.reward.into_iter().collect(),
+            duration: request.duration,
+
+            assigned_at: Some(env::time_now()),
+            accepted_at: None,
+            completed_at: None,
+            abandoned_at: None,
+        };
+
+        let _ignored = self.users.insert(author_id, author).map_err(storage)?;
+
+        // the author may be assigning themselves, so reload after the write above
+        let mut assignee = self.get_registered_user(&request.assignee)?;
+
+        let _ignored = bounty.assignments.insert(assignment_id).map_err(storage)?;
+        let _ignored = assignee
+            .assignments
+            .insert(assignment_id)
+            .map_err(storage)?;
+
+        self.stats.tally_assignment(&assignment, Change::Add)?;
+
+        let _ignored = self
+            .users
+            .insert(request.assignee, assignee)
+            .map_err(storage)?;
+        let _ignored = self
+            .bounties
+            .insert(request.bounty, bounty)
+            .map_err(storage)?;
+        let _ignored = self
+            .assignments
+            .insert(assignment_id, assignment)
+            .map_err(storage)?;
+
+        self.record_activity(
+            Some(request.bounty),
+            ActivityKind::AssignmentOffered {
+                assignment: assignment_id,
+                assignee: request.assignee,
+            },
+        )?;
+
+        app::emit!(Event::AssignmentOffered {
+            assignment: assignment_id,
+            bounty: request.bounty,
+            user: request.assignee,
+        });
+
+        Ok(assignment_id)
+    }
+
+    pub fn accept_assignment(&mut self, assignment_id: AssignmentId) -> app::Result<()> {
+        let mut assignment = self.internal_get_pending_assignment_as_assignee(assignment_id)?;
 
+        self.stats.tally_assignment(&assignment, Change::Remove)?;
+
+        assignment.status = AssignmentStatus::InProgress;
+        assignment.accepted_at = Some(env::time_now());
+
+        self.stats.tally_assignment(&assignment, Change::Add)?;
+
+        let bounty_id = assignment.bounty;
+
+        let _ignored = self
+            .assignments
+            .insert(assignment_id, assignment)
+            .map_err(storage)?;
+
+        self.record_activity(
+            Some(bounty_id),
+            ActivityKind::AssignmentAccepted {
+                assignment: assignment_id,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn decline_assignment(
+        &mut self,
+        assignment_id: AssignmentId,
+        reason: Option<String>,
+    ) -> app::Result<()> {
+        let mut assignment = self.internal_get_pending_assignment_as_assignee(assignment_id)?;
+
+        self.stats.tally_assignment(&assignment, Change::Remove)?;
+
+        assignment.status = AssignmentStatus::Abandoned { reason };
+        assignment.abandoned_at = Some(env::time_now());
+
+        self.stats.tally_assignment(&assignment, Change::Add)?;
+
+        let bounty_id = assignment.bounty;
+
+        let _ignored = self
+            .assignments
+            .insert(assignment_id, assignment)
+            .map_err(storage)?;
+
+        self.record_activity(
+            Some(bounty_id),
+            ActivityKind::AssignmentDeclined {
+                assignment: assignment_id,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn add_deliverable_links(
+        &mut self,
+        assignment_id: AssignmentId,
+        links: BTreeSet<String>,
+    ) -> app::Result<()> {
+        let mut assignment = self.internal_get_active_assignment_as_assignee(assignment_id)?;
+
+        let links = links
+            .iter()
+            .map(|link| validate_deliverable_link(&self.config, link))
+            .collect::<app::Result<BTreeSet<_>>>()?;
+
+        let mut got = assignment.links.len().map_err(storage)?;
+
+        for link in &links {
+            if !assignment.links.contains(link).map_err(storage)? {
+                got += 1;
+            }
+        }
+
+        if got > self.config.max_deliverable_links {
+            bail!(Error::DeliverableLinksLimit {
+                max: self.config.max_deliverable_links,
+                got,
+            });
+        }
+
+        for link in links {
+            let _ignored = assignment.links.insert(link).map_err(storage)?;
+        }
+
+        let _ignored = self
+            .assignments
+            .insert(assignment_id, assignment)
+            .map_err(storage)?;
+
+        Ok(())
+    }
+
+    pub fn remove_deliverable_link(
+        &mut self,
+        assignment_id: AssignmentId,
+        link: String,
+    ) -> app::Result<()> {
+        let mut assignment = self.internal_get_active_assignment_as_assignee(assignment_id)?;
+
+        let link = canonical_link(&link)?;
+
+        if !assignment.links.remove(&link).map_err(storage)? {
+            bail!(Error::DeliverableLinkNotFound {
+                assignment: assignment_id,
+                link,
+            });
+        }
+
+        let _ignored = self
+            .assignments
+            .insert(assignment_id, assignment)
+            .map_err(storage)?;
+
+        Ok(())
+    }
+
+    pub fn get_deliverable_links(&self, assignment_id: AssignmentId) -> app::Result<Vec<LinkView>> {
+        let assignment = self.internal_get_assignment(assignment_id)?;
+
+        let links = assignment
+            .links
+            .iter()
+            .map_err(storage)?
+            .map(LinkView::from)
+            .collect();
+
+        Ok(links)
+    }
+
+    pub fn get_assignment(&self, assignment_id: AssignmentId) -> app::Result<AssignmentView> {
+        let assignment = self.internal_get_assignment(assignment_id)?;
+
+        assignment_view(assignment_id, assignment)
+    }
+
+    pub fn list_assignments(
+        &self,
+        filter: Option<AssignmentFilter>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<AssignmentView>> {
+        let filter = filter.unwrap_or_default();
+
+        // start from the narrowest set the filter allows
+        let candidates = if let Some(bounty_id) = filter.bounty {
+            let bounty = self.internal_get_bounty(bounty_id)?;
+
+            bounty
+                .assignments
+                .iter()
+                .map_err(storage)?
+                .collect::<Vec<_>>()
+        } else if let Some(assignee) = filter.assignee {
+            let user = self.get_registered_user(&assignee)?;
+
+            user.assignments.iter().map_err(storage)?.collect()
+        } else {
+            self.assignments
+                .entries()
+                .map_err(storage)?
+                .map(|(id, _)| id)
+                .collect()
+        };
+
+        let mut assignments = Vec::new();
+
+        for assignment_id in candidates {
+            let Some(assignment) = self.assignments.get(&assignment_id).map_err(storage)? else {
+                continue;
+            };
+
+            if filter.matches(&assignment) {
+                assignments.push((assignment_id, assignment));
+            }
+        }
+
+        assignments.sort_by_key(|(_, assignment)| Reverse(assignment.assigned_at));
+
+        paginate(assignments, offset, length)
+            .into_iter()
+            .map(|(id, assignment)| assignment_view(id, assignment))
+            .collect()
+    }
+}
+
+fn assignment_view(
+    assignment_id: AssignmentId,
+    assignment: Assignment,
+) -> app::Result<AssignmentView> {
+    let now = env::time_now();
+
+    let due_at = match (assignment.accepted_at, assignment.duration) {
+        (Some(accepted_at), Some(duration)) => Some(accepted_at.saturating_add(duration)),
+        _ => None,
+    };
+
+    Ok(AssignmentView {
+        id: assignment_id,
+        assignee: assignment.assignee,
+        bounty: assignment.bounty,
+        message: assignment.message,
+        bid: assignment.bid,
+        links: assignment
+            .links
+            .iter()
+            .map_err(storage)?
+            .map(LinkView::from)
+            .collect(),
+        status: assignment.status,
+        expiry: assignment.expiry,
+        time_remaining: assignment.expiry.map(|expiry| expiry.saturating_sub(now)),
+        reward: assignment.reward.entries().map_err(storage)?.collect(),
+        duration: assignment.duration,
+        due_at,
+        assigned_at: assignment.assigned_at,
+        accepted_at: assignment.accepted_at,
+        completed_at: assignment.completed_at,
+        abandoned_at: assignment.abandoned_at,
+    })
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct AssignmentFilter {
+    #[serde(default)]
+    pub assignee: Option<UserId>,
+    #[serde(default)]
+    pub bounty: Option<BountyId>,
+    #[serde(default)]
+    pub status: Option<AssignmentStatusFilter>,
+}
+
+impl AssignmentFilter {
+    fn matches(&self, assignment: &Assignment) -> bool {
+        if self
+            .assignee
+            .is_some_and(|assignee| assignee != assignment.assignee)
+        {
+            return false;
+        }
+
+        if self
+            .bounty
+            .is_some_and(|bounty| bounty != assignment.bounty)
+        {
+            return false;
+        }
+
+        match &self.status {
+            Some(status) => status.against(&assignment.status),
+            None => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct AssignmentView {
+    pub id: AssignmentId,
+    pub assignee: UserId,
+    pub bounty: BountyId,
     pub message: MessageId,
     pub bid: Option<BidId>,
-    pub links: UnorderedSet<String>,
-
+    pub links: Vec<LinkView>,
     pub status: AssignmentStatus,
     pub expiry: Option<u64>,
-    pub reward: UnorderedMap<String, u128>,
+    /// Nanoseconds left before `expiry`, zero once it has passed.
+    pub time_remaining: Option<u64>,
+    pub reward: BTreeMap<String, u128>,
     pub duration: Option<u64>,
-
+    /// When the work is due, `duration` after it was accepted.
+    pub due_at: Option<u64>,
     pub assigned_at: Option<u64>,
     pub accepted_at: Option<u64>,
     pub completed_at: Option<u64>,
     pub abandoned_at: Option<u64>,
 }
-
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "calimero_sdk::borsh")]
-pub enum AssignmentStatus {
-    Received,
-    InProgress,
-    Completed,
-    Abandoned { reason: Option<String> },
-}
@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::collections::BTreeMap;
 
 use calimero_sdk::app;
@@ -6,46 +7,123 @@ use calimero_sdk::serde::{Deserialize, Serialize};
 use calimero_storage::collections::UnorderedMap;
 use thiserror::Error;
 
+use crate::activity::ActivityKind;
 use crate::assignment::AssignmentId;
-use crate::bounty::BountyId;
+use crate::bounty::{BountyId, SortOrder};
+use crate::config::AppConfig;
+use crate::env;
+use crate::error::{bail, storage, ErrorCode};
 use crate::message::{MessageId, MessageTarget};
-use crate::types::id::{self, IdExt};
+use crate::paging::paginate;
+use crate::schema;
+use crate::stats::Change;
+use crate::text::{self, TextKind};
+use crate::types::id;
 use crate::user::UserId;
-use crate::utils::unique;
 use crate::AppState;
 
-id::define!(pub BidId<8, 12>);
+id::define!(pub BidId<8, 12> as "bid");
 
-const MAX_BID_BRIEF_LENGTH: usize = 2_000;
-const MAX_BID_REWARD_RECIPIENTS: usize = 50;
+schema::versioned! {
+    #[derive(Debug)]
+    @version(2)
+    pub struct Bid {
+        pub author: UserId,
+        pub bounty: BountyId,
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "calimero_sdk::borsh")]
-pub struct Bid {
-    pub author: UserId,
-    pub bounty: BountyId,
+        pub message: MessageId,
+        pub assignment: Option<AssignmentId>,
 
-    pub message: MessageId,
-    pub assignment: Option<AssignmentId>,
+        pub status: BidStatus,
+        @since(2)
+        pub invited: bool,
+        pub expiry: Option<u64>,
+        pub reward: UnorderedMap<String, u128>,
+        pub duration: Option<u64>,
 
-    pub status: BidStatus,
-    pub expiry: Option<u64>,
-    pub reward: UnorderedMap<String, u128>,
-    pub duration: Option<u64>,
+        pub proposed_at: Option<u64>,
+        pub approved_at: Option<u64>,
+        pub retracted_at: Option<u64>,
 
-    pub proposed_at: Option<u64>,
-    pub approved_at: Option<u64>,
-    pub retracted_at: Option<u64>,
+        @since(2)
+        pub negotiation: Vec<CounterOffer>,
+    }
 }
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
+#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
 pub enum BidStatus {
     Proposed,
     Approved,
     Retracted { reason: Option<String> },
 }
 
+#[derive(
+    Clone, Copy, Debug, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum Party {
+    BountyAuthor,
+    Bidder,
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BidTerms {
+    pub reward: BTreeMap<String, u128>,
+    pub duration: Option<u64>,
+    pub expiry: Option<u64>,
+}
+
+#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum OfferStatus {
+    Open,
+    Accepted,
+    Superseded,
+}
+
+/// One round of negotiating a bid's terms, only the latest can be open.
+#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct CounterOffer {
+    pub from: Party,
+    pub terms: BidTerms,
+    pub status: OfferStatus,
+    pub offered_at: u64,
+    pub answered_at: Option<u64>,
+}
+
+impl CounterOffer {
+    fn awaits(&self, party: Party) -> bool {
+        matches!(self.status, OfferStatus::Open) && self.from != party
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum BidStatusFilter {
+    Proposed,
+    Approved,
+    Retracted,
+}
+
+impl BidStatusFilter {
+    pub fn against(&self, status: &BidStatus) -> bool {
+        matches!(
+            (self, status),
+            (Self::Proposed, BidStatus::Proposed)
+                | (Self::Approved, BidStatus::Approved)
+                | (Self::Retracted, BidStatus::Retracted { .. })
+        )
+    }
+}
+
 #[derive(Debug, Error, Serialize)]
 #[serde(crate = "calimero_sdk::serde")]
 #[serde(tag = "kind", content = "data")]
@@ -54,31 +132,187 @@ pub enum Error {
     BriefTooLong { got: usize, max: usize },
     #[error("too many reward recipients ({got} > {max})")]
     TooManyRewardRecipients { got: usize, max: usize },
+    #[error("bid not found: {0}")]
+    BidNotFound(BidId),
+    #[error("bid is no longer open: {0}")]
+    BidNotOpen(BidId),
+    #[error("only the bidder or the bounty author can negotiate {0}")]
+    NotNegotiating(BidId),
+    #[error("no counter-offer awaiting a reply on {0}")]
+    NoOpenOffer(BidId),
+    #[error("the other side has yet to reply to the last offer on {0}")]
+    AwaitingReply(BidId),
+    #[error("too many negotiation rounds (max {max})")]
+    TooManyRounds { max: usize },
+    #[error("expiry is in the past: {0}")]
+    ExpiryInPast(u64),
+    #[error("expiry is too far ahead: {expiry} (max {max})")]
+    ExpiryTooFar { expiry: u64, max: u64 },
+    #[error("the counter-offer on {0} has expired")]
+    OfferExpired(BidId),
+    #[error("bid has expired: {0}")]
+    BidExpired(BidId),
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::BriefTooLong { .. } => 3001,
+            Error::TooManyRewardRecipients { .. } => 3002,
+            Error::BidNotFound(_) => 3003,
+            Error::BidNotOpen(_) => 3004,
+            Error::NotNegotiating(_) => 3005,
+            Error::NoOpenOffer(_) => 3006,
+            Error::AwaitingReply(_) => 3007,
+            Error::TooManyRounds { .. } => 3008,
+            Error::ExpiryInPast(_) => 3009,
+            Error::ExpiryTooFar { .. } => 3010,
+            Error::OfferExpired(_) => 3011,
+            Error::BidExpired(_) => 3012,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::BidNotFound(id)
+            | Error::BidNotOpen(id)
+            | Error::NotNegotiating(id)
+            | Error::NoOpenOffer(id)
+            | Error::AwaitingReply(id)
+            | Error::OfferExpired(id)
+            | Error::BidExpired(id) => Some(id.to_string()),
+            Error::BriefTooLong { .. }
+            | Error::TooManyRewardRecipients { .. }
+            | Error::TooManyRounds { .. }
+            | Error::ExpiryInPast(_)
+            | Error::ExpiryTooFar { .. } => None,
+        }
+    }
 }
 
-fn validate_bid_brief(brief: &str) -> app::Result<()> {
-    if brief.len() > MAX_BID_BRIEF_LENGTH {
-        app::bail!(Error::BriefTooLong {
-            got: brief.len(),
-            max: MAX_BID_BRIEF_LENGTH,
+impl AppState {
+    pub fn internal_get_bid(&self, bid_id: BidId) -> app::Result<Bid> {
+        let Some(bid) = self.bids.get(&bid_id).map_err(storage)? else {
+            bail!(Error::BidNotFound(bid_id));
+        };
+
+        Ok(bid)
+    }
+
+    /// Loads a bid that is still up for negotiation, along with the side the
+    /// current user is on and the user on the other side.
+    fn internal_get_negotiable_bid(&self, bid_id: BidId) -> app::Result<(Bid, Party, UserId)> {
+        let bid = self.internal_get_bid(bid_id)?;
+
+        if !matches!(bid.status, BidStatus::Proposed) {
+            bail!(Error::BidNotOpen(bid_id));
+        }
+
+        if bid.expiry.is_some_and(|expiry| expiry <= env::time_now()) {
+            bail!(Error::BidExpired(bid_id));
+        }
+
+        let bounty = self.internal_get_open_bounty(bid.bounty)?;
+
+        let user_id = self.current_user();
+
+        let (party, counterparty) = if user_id == bounty.author {
+            (Party::BountyAuthor, bid.author)
+        } else if user_id == bid.author {
+            (Party::Bidder, bounty.author)
+        } else {
+            bail!(Error::NotNegotiating(bid_id));
+        };
+
+        Ok((bid, party, counterparty))
+    }
+
+    /// When the bid was placed. Bids from before `proposed_at` was recorded
+    /// fall back to their brief's timestamp.
+    pub fn internal_bid_proposed_at(&self, bid: &Bid) -> app::Result<Option<u64>> {
+        if bid.proposed_at.is_some() {
+            return Ok(bid.proposed_at);
+        }
+
+        Ok(self
+            .messages
+            .get(&bid.message)
+            .map_err(storage)?
+            .map(|message| message.timestamp))
+    }
+
+    fn internal_bid_view(&self, bid_id: BidId, bid: Bid) -> app::Result<BidView> {
+        let brief = self.internal_get_message(bid.message)?;
+
+        Ok(BidView {
+            id: bid_id,
+            author: bid.author,
+            bounty: bid.bounty,
+            brief: brief.content,
+            message: bid.message,
+            assignment: bid.assignment,
+            status: bid.status,
+            invited: bid.invited,
+            expiry: bid.expiry,
+            reward: bid.reward.entries().map_err(storage)?.collect(),
+            duration: bid.duration,
+            proposed_at: bid.proposed_at,
+            approved_at: bid.approved_at,
+            retracted_at: bid.retracted_at,
+            negotiation: bid.negotiation,
+        })
+    }
+}
+
+pub fn validate_bid_brief(config: &AppConfig, brief: &str) -> app::Result<String> {
+    let brief = text::normalize("brief", brief, TextKind::Multiline)?;
+
+    let got = text::measure(&brief, config.length_policy);
+
+    if got > config.max_bid_brief_length {
+        bail!(Error::BriefTooLong {
+            got,
+            max: config.max_bid_brief_length,
         });
     }
 
-    Ok(())
+    Ok(brief)
 }
 
-fn validate_bid_reward(reward_count: usize) -> app::Result<()> {
-    if reward_count > MAX_BID_REWARD_RECIPIENTS {
-        app::bail!(Error::TooManyRewardRecipients {
+pub fn validate_bid_reward(config: &AppConfig, reward_count: usize) -> app::Result<()> {
+    if reward_count > config.max_bid_reward_recipients {
+        bail!(Error::TooManyRewardRecipients {
             got: reward_count,
-            max: MAX_BID_REWARD_RECIPIENTS,
+            max: config.max_bid_reward_recipients,
         });
     }
 
     Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Applies to bids, counter-offers and direct assignments alike.
+pub fn validate_bid_expiry(config: &AppConfig, expiry: Option<u64>) -> app::Result<()> {
+    let Some(expiry) = expiry else {
+        return Ok(());
+    };
+
+    let now = env::time_now();
+
+    if expiry <= now {
+        bail!(Error::ExpiryInPast(expiry));
+    }
+
+    let max = now.saturating_add(config.max_expiry_horizon);
+
+    if expiry > max {
+        bail!(Error::ExpiryTooFar { expiry, max });
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
 #[serde(crate = "calimero_sdk::serde")]
 pub struct CreateBidRequest {
     pub brief: String,
@@ -95,17 +329,23 @@ impl AppState {
 
         let mut user = self.get_registered_user(&user_id)?;
 
-        validate_bid_brief(&request.brief)?;
-        validate_bid_reward(request.reward.len())?;
+        let brief = validate_bid_brief(&self.config, &request.brief)?;
+        validate_bid_reward(&self.config, request.reward.len())?;
+        validate_bid_expiry(&self.config, request.expiry)?;
 
-        let bid_id = unique(|| BidId::random(), |id| self.bids.contains(id))?;
+        let mut bounty = self.internal_get_open_bounty(request.bounty)?;
 
-        let message_id = self.internal_post_message(
-            user_id,
-            &mut user,
-            MessageTarget::Bid(bid_id),
-            request.brief,
-        )?;
+        let bid_id = self.ids.next(|id: &BidId| self.bids.contains(id))?;
+
+        // invited users skip the queue, their bid is flagged for the author
+        let invited = bounty.invitations.remove(&user_id).map_err(storage)?;
+
+        if invited {
+            let _ignored = user.invitations.remove(&request.bounty).map_err(storage)?;
+        }
+
+        let message_id =
+            self.internal_post_message(user_id, &mut user, MessageTarget::Bid(bid_id), brief)?;
 
         let bid = Bid {
             author: user_id,
@@ -116,20 +356,232 @@ impl AppState {
             assignment: None,
 
             status: BidStatus::Proposed,
+            invited,
             expiry: request.expiry,
             reward: request.reward.into_iter().collect(),
             duration: request.duration,
 
-            proposed_at: None,
+            proposed_at: Some(env::time_now()),
             approved_at: None,
             retracted_at: None,
+
+            negotiation: Vec::new(),
         };
 
-        let _ignored = user.bids.insert(bid_id)?;
+        let _ignored = user.bids.insert(bid_id).map_err(storage)?;
+        let _ignored = bounty.bids.insert(bid_id).map_err(storage)?;
+
+        self.stats.tally_bid(&bid, Change::Add)?;
 
-        let _ignored = self.users.insert(user_id, user)?;
-        let _ignored = self.bids.insert(bid_id, bid)?;
+        let _ignored = self.users.insert(user_id, user).map_err(storage)?;
+        let _ignored = self
+            .bounties
+            .insert(request.bounty, bounty)
+            .map_err(storage)?;
+        let _ignored = self.bids.insert(bid_id, bid).map_err(storage)?;
+
+        self.record_activity(
+            Some(request.bounty),
+            ActivityKind::BidPlaced { bid: bid_id },
+        )?;
 
         Ok(bid_id)
     }
+
+    /// Proposes different terms for a bid. The bounty author opens the
+    /// negotiation, after that either side can counter the other's offer.
+    pub fn counter_bid(&mut self, bid_id: BidId, terms: BidTerms) -> app::Result<()> {
+        let (mut bid, party, counterparty) = self.internal_get_negotiable_bid(bid_id)?;
+
+        validate_bid_reward(&self.config, terms.reward.len())?;
+        validate_bid_expiry(&self.config, terms.expiry)?;
+
+        if bid.negotiation.len() >= self.config.max_negotiation_rounds {
+            bail!(Error::TooManyRounds {
+                max: self.config.max_negotiation_rounds,
+            });
+        }
+
+        let now = env::time_now();
+
+        match bid.negotiation.last_mut() {
+            Some(offer) if offer.awaits(party) => {
+                offer.status = OfferStatus::Superseded;
+                offer.answered_at = Some(now);
+            }
+            Some(offer) if matches!(offer.status, OfferStatus::Open) => {
+                bail!(Error::AwaitingReply(bid_id));
+            }
+            // the bid itself is the bidder's offer
+            _ if party == Party::Bidder => bail!(Error::NoOpenOffer(bid_id)),
+            _ => {}
+        }
+
+        bid.negotiation.push(CounterOffer {
+            from: party,
+            terms,
+            status: OfferStatus::Open,
+            offered_at: now,
+            answered_at: None,
+        });
+
+        let bounty_id = bid.bounty;
+
+        let _ignored = self.bids.insert(bid_id, bid).map_err(storage)?;
+
+        self.record_activity(
+            Some(bounty_id),
+            ActivityKind::BidCountered {
+                bid: bid_id,
+                recipient: counterparty,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Accepts the other side's latest offer, which becomes the bid's terms.
+    pub fn accept_counter_offer(&mut self, bid_id: BidId) -> app::Result<()> {
+        let (mut bid, party, counterparty) = self.internal_get_negotiable_bid(bid_id)?;
+
+        let Some(offer) = bid
+            .negotiation
+            .last_mut()
+            .filter(|offer| offer.awaits(party))
+        else {
+            bail!(Error::NoOpenOffer(bid_id));
+        };
+
+        let now = env::time_now();
+
+        if offer.terms.expiry.is_some_and(|expiry| expiry <= now) {
+            bail!(Error::OfferExpired(bid_id));
+        }
+
+        offer.status = OfferStatus::Accepted;
+        offer.answered_at = Some(now);
+
+        let terms = offer.terms.clone();
+
+        bid.reward.clear().map_err(storage)?;
+
+        for (recipient, amount) in terms.reward {
+            let _ignored = bid.reward.insert(recipient, amount).map_err(storage)?;
+        }
+
+        bid.duration = terms.duration;
+        bid.expiry = terms.expiry;
+
+        let bounty_id = bid.bounty;
+
+        let _ignored = self.bids.insert(bid_id, bid).map_err(storage)?;
+
+        self.record_activity(
+            Some(bounty_id),
+            ActivityKind::CounterOfferAccepted {
+                bid: bid_id,
+                recipient: counterparty,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_bid(&self, bid_id: BidId) -> app::Result<BidView> {
+        let bid = self.internal_get_bid(bid_id)?;
+
+        self.internal_bid_view(bid_id, bid)
+    }
+
+    pub fn list_bids(
+        &self,
+        bounty_id: BountyId,
+        status: Option<BidStatusFilter>,
+        sortby: Option<BidSortBy>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<BidView>> {
+        let bounty = self.internal_get_bounty(bounty_id)?;
+
+        let mut bids = Vec::new();
+
+        for bid_id in bounty.bids.iter().map_err(storage)? {
+            let Some(bid) = self.bids.get(&bid_id).map_err(storage)? else {
+                continue;
+            };
+
+            if let Some(status) = &status {
+                if !status.against(&bid.status) {
+                    continue;
+                }
+            }
+
+            let total_reward = bid
+                .reward
+                .entries()
+                .map_err(storage)?
+                .fold(0_u128, |total, (_, amount)| total.saturating_add(amount));
+
+            bids.push((bid_id, total_reward, bid));
+        }
+
+        // newest first unless asked otherwise
+        bids.sort_by_key(|(_, _, bid)| Reverse(bid.proposed_at));
+
+        if let Some(sortby) = sortby {
+            bids.sort_by(|(_, a_reward, a), (_, b_reward, b)| {
+                let ord = match sortby.field {
+                    BidSortField::Reward => a_reward.cmp(b_reward),
+                    BidSortField::ProposedAt => a.proposed_at.cmp(&b.proposed_at),
+                    BidSortField::Duration => a.duration.cmp(&b.duration),
+                };
+
+                match sortby.order {
+                    SortOrder::Ascending => ord,
+                    SortOrder::Descending => ord.reverse(),
+                }
+            });
+        }
+
+        paginate(bids, offset, length)
+            .into_iter()
+            .map(|(bid_id, _, bid)| self.internal_bid_view(bid_id, bid))
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BidSortBy {
+    pub field: BidSortField,
+    pub order: SortOrder,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum BidSortField {
+    /// Total across all recipients.
+    Reward,
+    ProposedAt,
+    Duration,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BidView {
+    pub id: BidId,
+    pub author: UserId,
+    pub bounty: BountyId,
+    pub brief: String,
+    pub message: MessageId,
+    pub assignment: Option<AssignmentId>,
+    pub status: BidStatus,
+    pub invited: bool,
+    pub expiry: Option<u64>,
+    pub reward: BTreeMap<String, u128>,
+    pub duration: Option<u64>,
+    pub proposed_at: Option<u64>,
+    pub approved_at: Option<u64>,
+    pub retracted_at: Option<u64>,
+    pub negotiation: Vec<CounterOffer>,
 }
@@ -1,61 +1,69 @@
 use std::collections::BTreeSet;
 
+use calimero_sdk::app;
 use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use calimero_sdk::serde::{Deserialize, Serialize};
-use calimero_sdk::{app, env};
 use calimero_storage::collections::UnorderedSet;
 use thiserror::Error;
 
+use crate::activity::ActivityKind;
 use crate::assignment::AssignmentId;
 use crate::bid::BidId;
+use crate::config::AppConfig;
+use crate::env;
+use crate::error::{self, bail, storage, ErrorCode, InternalError, ItemResult};
+use crate::event::Event;
 use crate::message::{MessageId, MessageTarget};
-use crate::paging::ResumptionToken;
-use crate::types::id::{self, IdExt};
-use crate::user::UserId;
-use crate::utils::{truncate_string, unique};
+use crate::paging::{paginate, ResumptionToken};
+use crate::schema;
+use crate::stats::Change;
+use crate::text::{self, TextKind};
+use crate::types::id;
+use crate::user::{self, UserId, UserViewBrief};
+use crate::utils::truncate_string;
 use crate::{AppState, LabelId};
 
-id::define!(pub BountyId<8, 12>);
-
-const MAX_BOUNTY_TITLE_LENGTH: usize = 80;
-const MAX_BOUNTY_DESCRIPTION_LENGTH: usize = 10_000;
-const MAX_NUMBER_OF_REQUIRED_REVIEWS: usize = 20;
-const MAX_NUMBER_OF_LABELS: usize = 20;
-
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "calimero_sdk::borsh")]
-pub struct Bounty {
-    pub title: String,
-    pub author: UserId,
-    pub message: MessageId,
-
-    pub award: Option<u128>,
-    pub status: BountyStatus,
-    pub is_epic: bool,
-    pub deadline: Option<u64>,
-
-    pub labels: UnorderedSet<LabelId>,
-    pub reviewers: UnorderedSet<UserId>,
-
-    pub bids: UnorderedSet<BidId>,
-    pub assignments: UnorderedSet<AssignmentId>,
-
-    pub parent: Option<BountyId>,
-    pub children: UnorderedSet<BountyId>,
-
-    pub triaged_by: Option<UserId>,
-    pub approved_by: Option<UserId>,
-    pub closed_by: Option<UserId>,
-
-    pub proposed_at: Option<u64>,
-    pub triaged_at: Option<u64>,
-    pub approved_at: Option<u64>,
-    pub closed_at: Option<u64>,
-    pub updated_at: Option<u64>,
+id::define!(pub BountyId<8, 12> as "bty");
+
+schema::versioned! {
+    #[derive(Debug)]
+    @version(2)
+    pub struct Bounty {
+        pub title: String,
+        pub author: UserId,
+        pub message: MessageId,
+
+        pub award: Option<u128>,
+        pub status: BountyStatus,
+        pub is_epic: bool,
+        pub deadline: Option<u64>,
+
+        pub labels: UnorderedSet<LabelId>,
+        pub reviewers: UnorderedSet<UserId>,
+
+        pub bids: UnorderedSet<BidId>,
+        pub assignments: UnorderedSet<AssignmentId>,
+        @since(2)
+        pub invitations: UnorderedSet<UserId>,
+
+        pub parent: Option<BountyId>,
+        pub children: UnorderedSet<BountyId>,
+
+        pub triaged_by: Option<UserId>,
+        pub approved_by: Option<UserId>,
+        pub closed_by: Option<UserId>,
+
+        pub proposed_at: Option<u64>,
+        pub triaged_at: Option<u64>,
+        pub approved_at: Option<u64>,
+        pub closed_at: Option<u64>,
+        pub updated_at: Option<u64>,
+    }
 }
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
+#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
 pub enum BountyStatus {
     Proposed,
     Triaged,
@@ -63,8 +71,9 @@ pub enum BountyStatus {
     Closed { reason: ClosureReason },
 }
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
+#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
 pub enum ClosureReason {
     Completed { assignment: AssignmentId },
     Abandoned,
@@ -75,8 +84,8 @@ pub enum ClosureReason {
 #[serde(crate = "calimero_sdk::serde")]
 #[serde(tag = "kind", content = "data")]
 pub enum Error {
-    #[error("bounty not found")]
-    BountyNotFound,
+    #[error("bounty not found: {0}")]
+    BountyNotFound(BountyId),
     #[error("bounty title too long ({got} > {max})")]
     BountyTitleTooLong { got: usize, max: usize },
     #[error("bounty description too long ({got} > {max})")]
@@ -85,51 +94,124 @@ pub enum Error {
     BountyReviewersLimitExceeded { max: usize },
     #[error("bounty labels limit exceeded (max {max})")]
     BountyLabelsLimitExceeded { max: usize },
+    #[error("only the author of {0} can do this")]
+    NotBountyAuthor(BountyId),
+    #[error("bounty is closed: {0}")]
+    BountyClosed(BountyId),
+    #[error("the author of {0} can't be invited to it")]
+    CannotInviteAuthor(BountyId),
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::BountyNotFound(_) => 2001,
+            Error::BountyTitleTooLong { .. } => 2002,
+            Error::BountyDescriptionTooLong { .. } => 2003,
+            Error::BountyReviewersLimitExceeded { .. } => 2004,
+            Error::BountyLabelsLimitExceeded { .. } => 2005,
+            Error::NotBountyAuthor(_) => 2006,
+            Error::BountyClosed(_) => 2007,
+            Error::CannotInviteAuthor(_) => 2008,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::BountyNotFound(id)
+            | Error::NotBountyAuthor(id)
+            | Error::BountyClosed(id)
+            | Error::CannotInviteAuthor(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl AppState {
+    pub fn internal_get_bounty(&self, bounty_id: BountyId) -> app::Result<Bounty> {
+        let Some(bounty) = self.bounties.get(&bounty_id).map_err(storage)? else {
+            bail!(Error::BountyNotFound(bounty_id));
+        };
+
+        Ok(bounty)
+    }
+
+    pub fn internal_get_open_bounty(&self, bounty_id: BountyId) -> app::Result<Bounty> {
+        let bounty = self.internal_get_bounty(bounty_id)?;
+
+        if let BountyStatus::Closed { .. } = bounty.status {
+            bail!(Error::BountyClosed(bounty_id));
+        }
+
+        Ok(bounty)
+    }
+
+    pub fn internal_get_open_bounty_as_author(&self, bounty_id: BountyId) -> app::Result<Bounty> {
+        let bounty = self.internal_get_open_bounty(bounty_id)?;
+
+        if bounty.author != self.current_user() {
+            bail!(Error::NotBountyAuthor(bounty_id));
+        }
+
+        Ok(bounty)
+    }
 }
 
-fn validate_bounty_title(title: &str) -> app::Result<()> {
-    if title.len() > MAX_BOUNTY_TITLE_LENGTH {
-        app::bail!(Error::BountyTitleTooLong {
-            got: title.len(),
-            max: MAX_BOUNTY_TITLE_LENGTH,
+fn validate_bounty_title(config: &AppConfig, title: &str) -> app::Result<String> {
+    let title = text::normalize("title", title, TextKind::Line)?;
+
+    let got = text::measure(&title, config.length_policy);
+
+    if got > config.max_bounty_title_length {
+        bail!(Error::BountyTitleTooLong {
+            got,
+            max: config.max_bounty_title_length,
         });
     }
 
-    Ok(())
+    Ok(title)
 }
 
-fn validate_bounty_description(description: &str) -> app::Result<()> {
-    if description.len() > MAX_BOUNTY_DESCRIPTION_LENGTH {
-        app::bail!(Error::BountyDescriptionTooLong {
-            got: description.len(),
-            max: MAX_BOUNTY_DESCRIPTION_LENGTH,
+fn validate_bounty_description(config: &AppConfig, description: &str) -> app::Result<String> {
+    let description = text::normalize("description", description, TextKind::Multiline)?;
+
+    let got = text::measure(&description, config.length_policy);
+
+    if got > config.max_bounty_description_length {
+        bail!(Error::BountyDescriptionTooLong {
+            got,
+            max: config.max_bounty_description_length,
         });
     }
 
-    Ok(())
+    Ok(description)
 }
 
-fn validate_increment_bounty_reviewers(reviewers_count: usize) -> app::Result<()> {
-    if reviewers_count > MAX_NUMBER_OF_REQUIRED_REVIEWS {
-        app::bail!(Error::BountyReviewersLimitExceeded {
-            max: MAX_NUMBER_OF_REQUIRED_REVIEWS,
+fn validate_increment_bounty_reviewers(
+    config: &AppConfig,
+    reviewers_count: usize,
+) -> app::Result<()> {
+    if reviewers_count > config.max_bounty_reviewers {
+        bail!(Error::BountyReviewersLimitExceeded {
+            max: config.max_bounty_reviewers,
         });
     }
 
     Ok(())
 }
 
-fn validate_bounty_labels(labels_count: usize) -> app::Result<()> {
-    if labels_count > MAX_NUMBER_OF_LABELS {
-        app::bail!(Error::BountyLabelsLimitExceeded {
-            max: MAX_NUMBER_OF_LABELS,
+fn validate_bounty_labels(config: &AppConfig, labels_count: usize) -> app::Result<()> {
+    if labels_count > config.max_bounty_labels {
+        bail!(Error::BountyLabelsLimitExceeded {
+            max: config.max_bounty_labels,
         });
     }
 
     Ok(())
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
 #[serde(crate = "calimero_sdk::serde")]
 pub struct CreateBountyRequest {
     pub is_epic: bool,
@@ -149,24 +231,24 @@ impl AppState {
 
         let mut user = self.get_registered_user(&user_id)?;
 
-        validate_bounty_title(&request.title)?;
-        validate_bounty_description(&request.description)?;
-        validate_increment_bounty_reviewers(request.reviewers.len())?;
-        validate_bounty_labels(request.labels.len())?;
+        let title = validate_bounty_title(&self.config, &request.title)?;
+        let description = validate_bounty_description(&self.config, &request.description)?;
+        validate_increment_bounty_reviewers(&self.config, request.reviewers.len())?;
+        validate_bounty_labels(&self.config, request.labels.len())?;
 
-        let bounty_id = unique(|| BountyId::random(), |id| self.bounties.contains(id))?;
+        let bounty_id = self.ids.next(|id: &BountyId| self.bounties.contains(id))?;
 
         let message_id = self.internal_post_message(
             user_id,
             &mut user,
             MessageTarget::Bounty(bounty_id),
-            request.description,
+            description,
         )?;
 
         let now = env::time_now();
 
         let bounty = Bounty {
-            title: request.title,
+            title,
             author: user_id,
             message: message_id,
 
@@ -180,6 +262,7 @@ impl AppState {
 
             bids: UnorderedSet::new(),
             assignments: UnorderedSet::new(),
+            invitations: UnorderedSet::new(),
 
             parent: request.parent,
             children: UnorderedSet::new(),
@@ -195,13 +278,100 @@ impl AppState {
             updated_at: Some(now),
         };
 
-        let _ignored = user.bounties.insert(bounty_id)?;
+        let _ignored = user.bounties.insert(bounty_id).map_err(storage)?;
+
+        self.stats.tally_bounty(&bounty, Change::Add)?;
+
+        let _ignored = self.users.insert(user_id, user).map_err(storage)?;
+        let _ignored = self.bounties.insert(bounty_id, bounty).map_err(storage)?;
 
-        let _ignored = self.users.insert(user_id, user)?;
-        let _ignored = self.bounties.insert(bounty_id, bounty)?;
+        self.index_bounty(bounty_id)?;
+
+        self.record_activity(Some(bounty_id), ActivityKind::BountyCreated)?;
 
         Ok(bounty_id)
     }
+
+    pub fn edit_bounty(
+        &mut self,
+        bounty_id: BountyId,
+        title: Option<String>,
+        description: Option<String>,
+    ) -> app::Result<()> {
+        let mut bounty = self.internal_get_open_bounty_as_author(bounty_id)?;
+
+        let title = title
+            .map(|title| validate_bounty_title(&self.config, &title))
+            .transpose()?;
+
+        let description = description
+            .map(|description| validate_bounty_description(&self.config, &description))
+            .transpose()?;
+
+        self.unindex_bounty(bounty_id)?;
+
+        if let Some(title) = title {
+            bounty.title = title;
+        }
+
+        if let Some(description) = description {
+            let mut message = self.internal_get_message(bounty.message)?;
+
+            message.content = description;
+
+            let _ignored = self
+                .messages
+                .insert(bounty.message, message)
+                .map_err(storage)?;
+        }
+
+        bounty.updated_at = Some(env::time_now());
+
+        let _ignored = self.bounties.insert(bounty_id, bounty).map_err(storage)?;
+
+        self.index_bounty(bounty_id)?;
+
+        self.record_activity(Some(bounty_id), ActivityKind::BountyEdited)?;
+
+        Ok(())
+    }
+}
+
+#[app::logic]
+impl AppState {
+    pub fn invite_user(&mut self, bounty_id: BountyId, user_id: UserId) -> app::Result<()> {
+        let mut bounty = self.internal_get_open_bounty_as_author(bounty_id)?;
+
+        if user_id == bounty.author {
+            bail!(Error::CannotInviteAuthor(bounty_id));
+        }
+
+        let mut user = self.get_registered_user(&user_id)?;
+
+        if !bounty.invitations.insert(user_id).map_err(storage)? {
+            return Ok(());
+        }
+
+        let _ignored = user.invitations.insert(bounty_id).map_err(storage)?;
+
+        let _ignored = self.users.insert(user_id, user).map_err(storage)?;
+        let _ignored = self.bounties.insert(bounty_id, bounty).map_err(storage)?;
+
+        self.record_activity(Some(bounty_id), ActivityKind::UserInvited { user: user_id })?;
+
+        app::emit!(Event::UserInvited {
+            bounty: bounty_id,
+            user: user_id,
+        });
+
+        Ok(())
+    }
+
+    pub fn list_invitations(&self) -> app::Result<Vec<BountyId>> {
+        let user = self.get_registered_user(&self.current_user())?;
+
+        Ok(user.invitations.iter().map_err(storage)?.collect())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -230,6 +400,32 @@ impl From<&BountyStatus> for BountyStatusLite {
     }
 }
 
+impl AppState {
+    fn internal_bounty_brief(
+        &self,
+        bounty_id: BountyId,
+        bounty: Bounty,
+    ) -> Result<BountyViewBrief, InternalError> {
+        let Some(message) = self.messages.get(&bounty.message).map_err(storage)? else {
+            return Err(error::Error::DanglingReference {
+                from: bounty_id.to_string(),
+                to: bounty.message.to_string(),
+            }
+            .into());
+        };
+
+        let status = BountyStatusLite::from(&bounty.status);
+
+        Ok(BountyViewBrief {
+            id: bounty_id,
+            title: bounty.title,
+            author: bounty.author,
+            description: truncate_string(&message.content, 100).into(),
+            status,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "calimero_sdk::serde")]
 pub struct BountyViewBrief {
@@ -276,35 +472,38 @@ pub struct BountyView {
 #[app::logic]
 impl AppState {
     pub fn get_bounty_brief(&self, bounty_id: BountyId) -> app::Result<BountyViewBrief> {
-        let Some(bounty) = self.bounties.get(&bounty_id)? else {
-            app::bail!(Error::BountyNotFound);
-        };
+        let bounty = self.internal_get_bounty(bounty_id)?;
 
-        let message = self.internal_get_message(bounty.message)?;
+        Ok(self.internal_bounty_brief(bounty_id, bounty)?)
+    }
 
-        let status = BountyStatusLite::from(&bounty.status);
+    pub fn get_bounties_brief(
+        &self,
+        bounty_ids: Vec<BountyId>,
+    ) -> app::Result<Vec<ItemResult<BountyViewBrief, Error>>> {
+        let briefs = bounty_ids
+            .into_iter()
+            .map(|bounty_id| -> ItemResult<_, Error> {
+                let Some(bounty) = self.bounties.get(&bounty_id).map_err(storage)? else {
+                    return Err(Error::BountyNotFound(bounty_id).into());
+                };
 
-        Ok(BountyViewBrief {
-            id: bounty_id,
-            title: bounty.title,
-            author: bounty.author,
-            description: truncate_string(&message.content, 100).into(),
-            status,
-        })
+                Ok(self.internal_bounty_brief(bounty_id, bounty)?)
+            });
+
+        Ok(briefs.collect())
     }
 
     pub fn get_bounty(&self, bounty_id: BountyId) -> app::Result<BountyView> {
-        let Some(bounty) = self.bounties.get(&bounty_id)? else {
-            app::bail!(Error::BountyNotFound);
-        };
+        let bounty = self.internal_get_bounty(bounty_id)?;
 
         let status = BountyStatusLite::from(&bounty.status);
 
-        let labels = bounty.labels.iter()?.collect();
-        let reviewers = bounty.reviewers.iter()?.collect();
-        let bids = bounty.bids.iter()?.collect();
-        let assignments = bounty.assignments.iter()?.collect();
-        let children = bounty.children.iter()?.collect();
+        let labels = bounty.labels.iter().map_err(storage)?.collect();
+        let reviewers = bounty.reviewers.iter().map_err(storage)?.collect();
+        let bids = bounty.bids.iter().map_err(storage)?.collect();
+        let assignments = bounty.assignments.iter().map_err(storage)?.collect();
+        let children = bounty.children.iter().map_err(storage)?.collect();
 
         Ok(BountyView {
             id: bounty_id,
@@ -352,6 +551,26 @@ pub struct BountyFilter {
     assigned_to: Option<UserId>,
 }
 
+impl BountyFilter {
+    pub fn matches(&self, bounty: &Bounty) -> bool {
+        if let Some(author) = &self.author {
+            if bounty.author != *author {
+                return false;
+            }
+        }
+
+        if let Some(status) = &self.status {
+            if !status.against(&bounty.status) {
+                return false;
+            }
+        }
+
+        // the others
+
+        true
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "calimero_sdk::serde")]
 pub enum BountyStatusFilter {
@@ -364,7 +583,7 @@ pub enum BountyStatusFilter {
 }
 
 impl BountyStatusFilter {
-    fn against(&self, status: &BountyStatus) -> bool {
+    pub fn against(&self, status: &BountyStatus) -> bool {
         match (self, status) {
             (BountyStatusFilter::Proposed, BountyStatus::Proposed)
             | (BountyStatusFilter::Triaged, BountyStatus::Triaged)
@@ -401,11 +620,27 @@ pub enum SortField {
 
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "calimero_sdk::serde")]
-enum SortOrder {
+pub enum SortOrder {
     Ascending,
     Descending,
 }
 
+#[derive(Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BountyListEntry {
+    pub bounty: BountyViewBrief,
+    pub author: ItemResult<UserViewBrief, user::Error>,
+}
+
+/// Bare IDs by default, which serialize exactly as they always have.
+#[derive(Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(untagged)]
+pub enum BountyList {
+    Ids(Vec<BountyId>),
+    Briefs(Vec<ItemResult<BountyListEntry, Error>>),
+}
+
 #[app::logic]
 impl AppState {
     pub fn list_bounties(
@@ -415,37 +650,21 @@ impl AppState {
         sortby: Option<BountySortBy>,
         offset: Option<usize>,
         length: Option<usize>,
-    ) -> app::Result<(Vec<BountyId>, Option<ResumptionToken>)> {
+        briefs: Option<bool>,
+    ) -> app::Result<(BountyList, Option<ResumptionToken>)> {
         // todo! integrate paginated resumption
         let _resume = resume;
 
         // vv~~ condition if `resume` is defined
 
-        let bounties = self.bounties.entries()?;
-
-        let filtered = bounties
-            .map(|(id, bounty)| {
-                if let Some(filter) = &filter {
-                    if let Some(author) = &filter.author {
-                        if bounty.author != *author {
-                            return Ok(None);
-                        }
-                    }
+        let bounties = self.bounties.entries().map_err(storage)?;
 
-                    if let Some(status) = &filter.status {
-                        if !status.against(&bounty.status) {
-                            return Ok(None);
-                        }
-                    }
-
-                    // the others
-                }
-
-                Ok(Some((id, bounty)))
-            })
-            .filter_map(|e| e.transpose());
+        let filtered = bounties.filter(|(_, bounty)| match &filter {
+            Some(filter) => filter.matches(bounty),
+            None => true,
+        });
 
-        let mut bounties = filtered.collect::<app::Result<Vec<_>>>()?;
+        let mut bounties = filtered.collect::<Vec<_>>();
 
         if let Some(sortby) = sortby {
             bounties.sort_by(|(_, a), (_, b)| {
@@ -470,13 +689,25 @@ impl AppState {
 
         // ^^~~ condition if `resume` is defined
 
-        let offset = offset.unwrap_or_default();
-        let length = length.unwrap_or(bounties.len());
+        let bounties = paginate(bounties, offset, length);
+
+        if !briefs.unwrap_or_default() {
+            let ids = bounties.into_iter().map(|(id, _)| id).collect();
 
-        let bounties = bounties.into_iter().map(|(id, _)| id);
+            return Ok((BountyList::Ids(ids), resume));
+        }
+
+        let entries = bounties
+            .into_iter()
+            .map(|(id, bounty)| -> ItemResult<_, Error> {
+                let author = self.internal_get_user_brief(bounty.author);
 
-        let bounties = bounties.skip(offset).take(length).collect();
+                let bounty = self.internal_bounty_brief(id, bounty)?;
+
+                Ok(BountyListEntry { bounty, author })
+            })
+            .collect();
 
-        Ok((bounties, resume))
+        Ok((BountyList::Briefs(entries), resume))
     }
 }
@@ -0,0 +1,140 @@
+//! Per-context tunables.
+//!
+//! Limits used to be compile-time constants; they now live in [`AppConfig`],
+//! seeded with the historical values at `init` and adjustable by admins, so
+//! contexts can tune them without rebuilding the app.
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::error::{bail, ErrorCode};
+use crate::text::LengthPolicy;
+use crate::AppState;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("config limit `{0}` must be above zero")]
+    ZeroLimit(&'static str),
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::ZeroLimit(_) => 6003,
+        }
+    }
+}
+
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(default)]
+pub struct AppConfig {
+    /// How text lengths are measured against the limits below.
+    pub length_policy: LengthPolicy,
+
+    pub max_user_name_length: usize,
+    pub max_user_skill_length: usize,
+    pub max_user_link_length: usize,
+
+    pub max_bounty_title_length: usize,
+    pub max_bounty_description_length: usize,
+    pub max_bounty_reviewers: usize,
+    pub max_bounty_labels: usize,
+
+    pub max_bid_brief_length: usize,
+    pub max_bid_reward_recipients: usize,
+    /// How far ahead bids and assignments may expire, in nanoseconds.
+    pub max_expiry_horizon: u64,
+    /// Counter-offers a single bid may go through.
+    pub max_negotiation_rounds: usize,
+
+    pub max_deliverable_link_length: usize,
+    pub max_deliverable_links: usize,
+
+    pub max_message_length: usize,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            length_policy: LengthPolicy::default(),
+
+            max_user_name_length: 100,
+            max_user_skill_length: 20,
+            max_user_link_length: 300,
+
+            max_bounty_title_length: 80,
+            max_bounty_description_length: 10_000,
+            max_bounty_reviewers: 20,
+            max_bounty_labels: 20,
+
+            max_bid_brief_length: 2_000,
+            max_bid_reward_recipients: 50,
+            max_expiry_horizon: 365 * 24 * 60 * 60 * 1_000_000_000,
+            max_negotiation_rounds: 20,
+
+            max_deliverable_link_length: 300,
+            max_deliverable_links: 20,
+
+            max_message_length: 1000,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Rejects limits nothing could ever satisfy. Count limits may be zero,
+    /// that only turns the feature off.
+    pub fn validate(&self) -> app::Result<()> {
+        let limits = [
+            ("max_user_name_length", self.max_user_name_length),
+            ("max_user_skill_length", self.max_user_skill_length),
+            ("max_user_link_length", self.max_user_link_length),
+            ("max_bounty_title_length", self.max_bounty_title_length),
+            (
+                "max_bounty_description_length",
+                self.max_bounty_description_length,
+            ),
+            ("max_bid_brief_length", self.max_bid_brief_length),
+            (
+                "max_deliverable_link_length",
+                self.max_deliverable_link_length,
+            ),
+            ("max_message_length", self.max_message_length),
+        ];
+
+        for (name, limit) in limits {
+            if limit == 0 {
+                bail!(Error::ZeroLimit(name));
+            }
+        }
+
+        if self.max_expiry_horizon == 0 {
+            bail!(Error::ZeroLimit("max_expiry_horizon"));
+        }
+
+        Ok(())
+    }
+}
+
+#[app::logic]
+impl AppState {
+    pub fn get_config(&self) -> app::Result<AppConfig> {
+        Ok(self.config.clone())
+    }
+
+    /// Replaces the whole config, existing entities are not re-validated.
+    pub fn update_config(&mut self, config: AppConfig) -> app::Result<()> {
+        self.ensure_admin(&self.current_user())?;
+
+        config.validate()?;
+
+        self.config = config;
+
+        Ok(())
+    }
+}
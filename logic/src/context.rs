@@ -0,0 +1,189 @@
+//! Context bootstrap and descriptive metadata.
+
+use std::collections::BTreeSet;
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::AppConfig;
+use crate::env;
+use crate::error::{bail, storage, ErrorCode};
+use crate::label::{Label, LabelId};
+use crate::text::{self, TextKind};
+use crate::user::UserId;
+use crate::AppState;
+
+#[derive(Debug, Default, BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct ContextInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub created_by: Option<UserId>,
+    pub created_at: Option<u64>,
+}
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("bootstrap {0} cannot be empty")]
+    EmptyBootstrapValue(&'static str),
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::EmptyBootstrapValue(_) => 6004,
+        }
+    }
+}
+
+/// Optional payload accepted by `init`, so a context comes up ready to use.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(default)]
+pub struct Bootstrap {
+    /// Admins in addition to the creator of the context.
+    pub admins: BTreeSet<UserId>,
+    pub labels: BTreeSet<String>,
+    pub config: Option<AppConfig>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+fn normalize_bootstrap_value(
+    field: &'static str,
+    value: &str,
+    kind: TextKind,
+) -> app::Result<String> {
+    let value = text::normalize(field, value, kind)?;
+
+    if value.is_empty() {
+        bail!(Error::EmptyBootstrapValue(field));
+    }
+
+    Ok(value)
+}
+
+impl Bootstrap {
+    /// Checks every value and returns the payload as it will be stored, with
+    /// labels that normalise to the same name merged.
+    fn validate(self) -> app::Result<Self> {
+        if let Some(config) = &self.config {
+            config.validate()?;
+        }
+
+        let labels = self
+            .labels
+            .iter()
+            .map(|name| normalize_bootstrap_value("label", name, TextKind::Line))
+            .collect::<app::Result<_>>()?;
+
+        let name = self
+            .name
+            .map(|name| normalize_bootstrap_value("name", &name, TextKind::Line))
+            .transpose()?;
+
+        let description = self
+            .description
+            .map(|description| {
+                normalize_bootstrap_value("description", &description, TextKind::Multiline)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            admins: self.admins,
+            labels,
+            config: self.config,
+            name,
+            description,
+        })
+    }
+}
+
+impl AppState {
+    /// Applies `bootstrap` to a fresh context. The whole payload is validated
+    /// before anything is stored, so `init` fails rather than coming up with
+    /// part of it.
+    pub fn bootstrap(&mut self, bootstrap: Bootstrap) -> app::Result<()> {
+        let bootstrap = bootstrap.validate()?;
+
+        let creator = self.current_user();
+
+        let _ignored = self.admins.insert(creator).map_err(storage)?;
+
+        for admin in bootstrap.admins {
+            let _ignored = self.admins.insert(admin).map_err(storage)?;
+        }
+
+        if let Some(config) = bootstrap.config {
+            self.config = config;
+        }
+
+        for name in bootstrap.labels {
+            let label_id = self.ids.next(|id: &LabelId| self.labels.contains(id))?;
+
+            let _ignored = self
+                .labels
+                .insert(label_id, Label { name })
+                .map_err(storage)?;
+        }
+
+        self.context = ContextInfo {
+            name: bootstrap.name,
+            description: bootstrap.description,
+            created_by: Some(creator),
+            created_at: Some(env::time_now()),
+        };
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct LabelView {
+    pub id: LabelId,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct ContextInfoView {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub created_by: Option<UserId>,
+    pub created_at: Option<u64>,
+    pub admins: Vec<UserId>,
+    pub labels: Vec<LabelView>,
+    pub config: AppConfig,
+}
+
+#[app::logic]
+impl AppState {
+    pub fn get_context_info(&self) -> app::Result<ContextInfoView> {
+        let mut labels = self
+            .labels
+            .entries()
+            .map_err(storage)?
+            .map(|(id, label)| LabelView {
+                id,
+                name: label.name,
+            })
+            .collect::<Vec<_>>();
+
+        labels.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(ContextInfoView {
+            name: self.context.name.clone(),
+            description: self.context.description.clone(),
+            created_by: self.context.created_by,
+            created_at: self.context.created_at,
+            admins: self.admins.iter().map_err(storage)?.collect(),
+            labels,
+            config: self.config.clone(),
+        })
+    }
+}
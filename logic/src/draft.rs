@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::alloc::IdAllocator;
+use crate::bid::{BidId, CreateBidRequest};
+use crate::bounty::{BountyId, CreateBountyRequest};
+use crate::env;
+use crate::error::{bail, ErrorCode};
+use crate::private::PrivateRootState;
+use crate::types::id;
+use crate::AppState;
+
+id::define!(pub DraftId<8, 12> as "dft");
+
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct Drafts {
+    pub bounties: BTreeMap<DraftId, Draft<CreateBountyRequest>>,
+    pub bids: BTreeMap<DraftId, Draft<CreateBidRequest>>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct Draft<T> {
+    pub request: T,
+    pub saved_at: u64,
+}
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("draft not found: {0}")]
+    DraftNotFound(DraftId),
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::DraftNotFound(_) => 7001,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::DraftNotFound(id) => Some(id.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct DraftView<T> {
+    pub id: DraftId,
+    pub request: T,
+    pub saved_at: u64,
+}
+
+fn save_draft<T>(
+    ids: &mut IdAllocator,
+    drafts: &mut BTreeMap<DraftId, Draft<T>>,
+    draft_id: Option<DraftId>,
+    request: T,
+) -> app::Result<DraftId> {
+    let draft_id = match draft_id {
+        Some(draft_id) => {
+            if !drafts.contains_key(&draft_id) {
+                bail!(Error::DraftNotFound(draft_id));
+            }
+
+            draft_id
+        }
+        None => ids.next(|id: &DraftId| drafts.contains_key(id))?,
+    };
+
+    let draft = Draft {
+        request,
+        saved_at: env::time_now(),
+    };
+
+    let _ignored = drafts.insert(draft_id, draft);
+
+    Ok(draft_id)
+}
+
+fn list_drafts<T: Clone>(drafts: &BTreeMap<DraftId, Draft<T>>) -> Vec<DraftView<T>> {
+    drafts
+        .iter()
+        .map(|(id, draft)| DraftView {
+            id: *id,
+            request: draft.request.clone(),
+            saved_at: draft.saved_at,
+        })
+        .collect()
+}
+
+fn get_draft<T: Clone>(drafts: &BTreeMap<DraftId, Draft<T>>, draft_id: DraftId) -> app::Result<T> {
+    let Some(draft) = drafts.get(&draft_id) else {
+        bail!(Error::DraftNotFound(draft_id));
+    };
+
+    Ok(draft.request.clone())
+}
+
+#[app::logic]
+impl AppState {
+    pub fn save_bounty_draft(
+        &mut self,
+        draft_id: Option<DraftId>,
+        request: CreateBountyRequest,
+    ) -> app::Result<DraftId> {
+        let mut root = PrivateRootState::load()?;
+
+        let mut state = root.as_mut();
+
+        let draft_id = save_draft(&mut self.ids, &mut state.drafts.bounties, draft_id, request)?;
+
+        state.commit()?;
+
+        Ok(draft_id)
+    }
+
+    pub fn list_bounty_drafts(&self) -> app::Result<Vec<DraftView<CreateBountyRequest>>> {
+        let root = PrivateRootState::load()?;
+
+        Ok(list_drafts(&root.drafts.bounties))
+    }
+
+    pub fn discard_bounty_draft(&mut self, draft_id: DraftId) -> app::Result<()> {
+        let mut root = PrivateRootState::load()?;
+
+        let mut state = root.as_mut();
+
+        if state.drafts.bounties.remove(&draft_id).is_none() {
+            bail!(Error::DraftNotFound(draft_id));
+        }
+
+        state.commit()
+    }
+
+    pub fn publish_bounty_draft(&mut self, draft_id: DraftId) -> app::Result<BountyId> {
+        let mut root = PrivateRootState::load()?;
+
+        let request = get_draft(&root.drafts.bounties, draft_id)?;
+
+        let bounty_id = self.create_bounty(request)?;
+
+        let mut state = root.as_mut();
+
+        let _ignored = state.drafts.bounties.remove(&draft_id);
+
+        state.commit()?;
+
+        Ok(bounty_id)
+    }
+
+    pub fn save_bid_draft(
+        &mut self,
+        draft_id: Option<DraftId>,
+        request: CreateBidRequest,
+    ) -> app::Result<DraftId> {
+        let mut root = PrivateRootState::load()?;
+
+        let mut state = root.as_mut();
+
+        let draft_id = save_draft(&mut self.ids, &mut state.drafts.bids, draft_id, request)?;
+
+        state.commit()?;
+
+        Ok(draft_id)
+    }
+
+    pub fn list_bid_drafts(&self) -> app::Result<Vec<DraftView<CreateBidRequest>>> {
+        let root = PrivateRootState::load()?;
+
+        Ok(list_drafts(&root.drafts.bids))
+    }
+
+    pub fn discard_bid_draft(&mut self, draft_id: DraftId) -> app::Result<()> {
+        let mut root = PrivateRootState::load()?;
+
+        let mut state = root.as_mut();
+
+        if state.drafts.bids.remove(&draft_id).is_none() {
+            bail!(Error::DraftNotFound(draft_id));
+        }
+
+        state.commit()
+    }
+
+    pub fn publish_bid_draft(&mut self, draft_id: DraftId) -> app::Result<BidId> {
+        let mut root = PrivateRootState::load()?;
+
+        let request = get_draft(&root.drafts.bids, draft_id)?;
+
+        let bid_id = self.create_bid(request)?;
+
+        let mut state = root.as_mut();
+
+        let _ignored = state.drafts.bids.remove(&draft_id);
+
+        state.commit()?;
+
+        Ok(bid_id)
+    }
+}
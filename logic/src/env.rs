@@ -0,0 +1,89 @@
+//! Host functions used by the app.
+//!
+//! Everything is forwarded to [`calimero_sdk::env`], except under `cfg(test)`
+//! where the host is replaced by [`mock`], so the logic can be exercised
+//! natively with a controllable clock, executor and RNG.
+
+#[cfg(not(test))]
+pub use calimero_sdk::env::{
+    executor_id, random_bytes, storage_read, storage_remove, storage_write, time_now,
+};
+#[cfg(test)]
+pub use mock::{executor_id, random_bytes, storage_read, storage_remove, storage_write, time_now};
+
+#[cfg(test)]
+pub mod mock {
+    use std::cell::{Cell, RefCell};
+    use std::collections::BTreeMap;
+
+    pub const DEFAULT_TIME: u64 = 1_700_000_000_000_000_000;
+    pub const DEFAULT_SEED: u64 = 0x5EED;
+
+    thread_local! {
+        static STORAGE: RefCell<BTreeMap<Vec<u8>, Vec<u8>>> = const { RefCell::new(BTreeMap::new()) };
+        static EXECUTOR: Cell<[u8; 32]> = const { Cell::new([0; 32]) };
+        static CLOCK: Cell<u64> = const { Cell::new(DEFAULT_TIME) };
+        static RNG: Cell<u64> = const { Cell::new(DEFAULT_SEED) };
+    }
+
+    pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
+        STORAGE.with_borrow(|storage| storage.get(key).cloned())
+    }
+
+    pub fn storage_write(key: &[u8], value: &[u8]) -> bool {
+        STORAGE.with_borrow_mut(|storage| storage.insert(key.to_vec(), value.to_vec()).is_some())
+    }
+
+    pub fn storage_remove(key: &[u8]) -> bool {
+        STORAGE.with_borrow_mut(|storage| storage.remove(key).is_some())
+    }
+
+    pub fn storage_keys() -> Vec<Vec<u8>> {
+        STORAGE.with_borrow(|storage| storage.keys().cloned().collect())
+    }
+
+    pub fn executor_id() -> [u8; 32] {
+        EXECUTOR.get()
+    }
+
+    pub fn set_executor_id(id: [u8; 32]) {
+        EXECUTOR.set(id);
+    }
+
+    pub fn time_now() -> u64 {
+        CLOCK.get()
+    }
+
+    pub fn set_time(now: u64) {
+        CLOCK.set(now);
+    }
+
+    pub fn advance_time(by: u64) {
+        CLOCK.set(CLOCK.get() + by);
+    }
+
+    /// Fills `buf` from a splitmix64 stream, so runs are reproducible per seed.
+    pub fn random_bytes(buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let mut z = RNG.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+            RNG.set(z);
+
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+
+            chunk.copy_from_slice(&z.to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    pub fn seed_rng(seed: u64) {
+        RNG.set(seed);
+    }
+
+    pub fn reset() {
+        STORAGE.with_borrow_mut(BTreeMap::clear);
+        EXECUTOR.set([0; 32]);
+        CLOCK.set(DEFAULT_TIME);
+        RNG.set(DEFAULT_SEED);
+    }
+}
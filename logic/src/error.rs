@@ -0,0 +1,207 @@
+//! Errors returned over RPC.
+//!
+//! Every error the app raises itself is wrapped in an [`AppError`], which
+//! serializes as:
+//!
+//! ```json
+//! {
+//!   "code": 2001,
+//!   "message": "bounty not found: bty_8bHQ3m9PXjB",
+//!   "entity": "bty_8bHQ3m9PXjB",
+//!   "kind": "BountyNotFound",
+//!   "data": "bty_8bHQ3m9PXjB"
+//! }
+//! ```
+//!
+//! `code` is stable and is what clients should switch on, `kind` and `data`
+//! carry the variant of the originating module error, and `entity` is the
+//! prefixed ID of the offending entity, when there is one.
+//!
+//! Codes are grouped by module, and are never reused once retired:
+//!
+//! | range | module       |
+//! |-------|--------------|
+//! | 1xxx  | users        |
+//! | 2xxx  | bounties     |
+//! | 3xxx  | bids         |
+//! | 4xxx  | assignments  |
+//! | 5xxx  | messages     |
+//! | 6xxx  | admin        |
+//! | 7xxx  | drafts       |
+//! | 8xxx  | validation   |
+//! | 9xxx  | internal     |
+//! | 10xxx | paging       |
+//! | 11xxx | snapshots    |
+//!
+//! Failures of the underlying storage are wrapped the same way, as
+//! [`Error::Storage`], by propagating them with `.map_err(storage)?`.
+
+use core::fmt;
+
+use calimero_sdk::serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("storage error: {0}")]
+    Storage(String),
+    #[error("{from} refers to {to}, which does not exist")]
+    DanglingReference { from: String, to: String },
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::Storage(_) => 9003,
+            Error::DanglingReference { .. } => 9004,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::Storage(_) => None,
+            Error::DanglingReference { from, .. } => Some(from.clone()),
+        }
+    }
+}
+
+/// An [`Error`] in its envelope.
+pub type InternalError = AppError<Error>;
+
+pub trait ErrorCode {
+    /// Stable identifier of this error, part of the RPC contract.
+    fn code(&self) -> u16;
+
+    /// Prefixed ID of the entity the error is about, if any.
+    fn entity(&self) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct AppError<E> {
+    pub code: u16,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity: Option<String>,
+    #[serde(flatten)]
+    pub error: E,
+}
+
+impl<E: ErrorCode + fmt::Display> From<E> for AppError<E> {
+    fn from(error: E) -> Self {
+        Self {
+            code: error.code(),
+            message: error.to_string(),
+            entity: error.entity(),
+            error,
+        }
+    }
+}
+
+impl<E> fmt::Display for AppError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for AppError<E> {}
+
+impl<E> AppError<E> {
+    fn map<F>(self, f: impl FnOnce(E) -> F) -> AppError<F> {
+        AppError {
+            code: self.code,
+            message: self.message,
+            entity: self.entity,
+            error: f(self.error),
+        }
+    }
+}
+
+/// Why one item of a batch call failed, either an error of the module the
+/// item belongs to or an internal one. Serialized as the error it wraps.
+#[derive(Debug, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(untagged)]
+pub enum ItemError<E> {
+    Item(E),
+    Internal(Error),
+}
+
+impl<E: ErrorCode + fmt::Display> From<E> for AppError<ItemError<E>> {
+    fn from(error: E) -> Self {
+        AppError::<E>::from(error).map(ItemError::Item)
+    }
+}
+
+impl<E> From<InternalError> for AppError<ItemError<E>> {
+    fn from(error: InternalError) -> Self {
+        error.map(ItemError::Internal)
+    }
+}
+
+/// Outcome of one item of a batch call, serialized as `{"Ok": ..}` or
+/// `{"Err": ..}`, so one item failing doesn't fail the rest of the batch.
+pub type ItemResult<T, E> = Result<T, AppError<ItemError<E>>>;
+
+/// Like [`calimero_sdk::app::bail!`], wrapping the error in an [`AppError`].
+macro_rules! bail {
+    ($error:expr $(,)?) => {
+        ::calimero_sdk::app::bail!($crate::error::AppError::from($error))
+    };
+}
+
+pub(crate) use bail;
+
+/// Wraps a storage failure in an [`AppError`].
+pub fn storage(error: impl fmt::Display) -> InternalError {
+    AppError::from(Error::Storage(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounty::{self, BountyId};
+
+    #[test]
+    fn envelope_carries_code_and_entity() {
+        let bounty_id = BountyId::new(*b"bounty!!");
+
+        let error = AppError::from(bounty::Error::BountyNotFound(bounty_id));
+
+        assert_eq!(error.code, 2001);
+        assert_eq!(error.entity, Some(bounty_id.to_string()));
+        assert_eq!(error.message, format!("bounty not found: {bounty_id}"));
+    }
+
+    #[test]
+    fn storage_failures_share_the_envelope() {
+        let error = storage("disk on fire");
+
+        assert_eq!(error.code, 9003);
+        assert_eq!(error.entity, None);
+        assert_eq!(error.message, "storage error: disk on fire");
+    }
+
+    #[test]
+    fn item_errors_keep_the_code_of_what_they_wrap() {
+        let bounty_id = BountyId::new(*b"bounty!!");
+
+        let error: AppError<ItemError<bounty::Error>> =
+            bounty::Error::BountyNotFound(bounty_id).into();
+
+        assert_eq!(error.code, 2001);
+        assert!(matches!(error.error, ItemError::Item(_)));
+
+        let error: AppError<ItemError<bounty::Error>> = storage("disk on fire").into();
+
+        assert_eq!(error.code, 9003);
+        assert!(matches!(
+            error.error,
+            ItemError::Internal(Error::Storage(_))
+        ));
+    }
+}
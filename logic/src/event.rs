@@ -1,4 +1,18 @@
 use calimero_sdk::app;
 
+use crate::assignment::AssignmentId;
+use crate::bounty::BountyId;
+use crate::user::UserId;
+
 #[app::event]
-pub enum Event {}
+pub enum Event {
+    UserInvited {
+        bounty: BountyId,
+        user: UserId,
+    },
+    AssignmentOffered {
+        assignment: AssignmentId,
+        bounty: BountyId,
+        user: UserId,
+    },
+}
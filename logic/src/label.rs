@@ -1,13 +1,15 @@
-use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use calimero_sdk::serde::{Deserialize, Serialize};
 
+use crate::schema;
 use crate::types::id;
 
-id::define!(pub LabelId<8, 12>);
+id::define!(pub LabelId<8, 12> as "lbl");
 
-#[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
-#[borsh(crate = "calimero_sdk::borsh")]
-#[serde(crate = "calimero_sdk::serde")]
-pub struct Label {
-    pub name: String,
+schema::versioned! {
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(crate = "calimero_sdk::serde")]
+    @version(1)
+    pub struct Label {
+        pub name: String,
+    }
 }
@@ -1,43 +1,94 @@
 use calimero_sdk::app;
-use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use calimero_storage::collections::UnorderedMap;
+use calimero_storage::collections::{UnorderedMap, UnorderedSet};
 
+mod activity;
+mod admin;
+mod alloc;
 mod assignment;
 mod bid;
 mod bounty;
+mod config;
+mod context;
+mod draft;
+mod env;
+mod error;
 mod event;
 mod label;
+mod link;
 mod message;
+mod notification;
 mod paging;
+mod preferences;
 mod private;
+mod schema;
+mod search;
+mod skill;
+mod snapshot;
+mod stats;
+#[cfg(test)]
+mod testing;
+mod text;
 mod types;
 mod user;
 mod utils;
+mod watchlist;
 
+use activity::{ActivityId, ActivityLog};
+use alloc::IdAllocator;
 use assignment::{Assignment, AssignmentId};
 use bid::{Bid, BidId};
 use bounty::{Bounty, BountyId};
+use config::AppConfig;
+use context::{Bootstrap, ContextInfo};
 use event::Event;
 use label::{Label, LabelId};
 use message::{Message, MessageId};
-use user::{User, UserId};
+use notification::Notification;
+use stats::Stats;
+use user::{User, UserEdit, UserEditId, UserId};
 
-#[app::state(emits = Event)]
-#[derive(Default, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "calimero_sdk::borsh")]
-pub struct AppState {
-    users: UnorderedMap<UserId, User>,
-    bids: UnorderedMap<BidId, Bid>,
-    assignments: UnorderedMap<AssignmentId, Assignment>,
-    bounties: UnorderedMap<BountyId, Bounty>,
-    messages: UnorderedMap<MessageId, Message>,
-    labels: UnorderedMap<LabelId, Label>,
+schema::versioned! {
+    #[app::state(emits = Event)]
+    #[derive(Default)]
+    @version(5)
+    pub struct AppState {
+        @since(2)
+        ids: IdAllocator,
+        @since(2)
+        config: AppConfig,
+        @since(2)
+        context: ContextInfo,
+        @since(2)
+        admins: UnorderedSet<UserId>,
+        users: UnorderedMap<UserId, User>,
+        @since(2)
+        user_edits: UnorderedMap<UserEditId, UserEdit>,
+        @since(2)
+        skills: UnorderedMap<String, UnorderedSet<UserId>>,
+        bids: UnorderedMap<BidId, Bid>,
+        assignments: UnorderedMap<AssignmentId, Assignment>,
+        bounties: UnorderedMap<BountyId, Bounty>,
+        messages: UnorderedMap<MessageId, Message>,
+        labels: UnorderedMap<LabelId, Label>,
+        @since(2)
+        search: UnorderedMap<String, UnorderedMap<BountyId, u32>>,
+        @since(3)
+        stats: Stats,
+        @since(4)
+        activity: ActivityLog,
+        @since(5)
+        notifications: UnorderedMap<UserId, UnorderedMap<ActivityId, Notification>>,
+    }
 }
 
 #[app::logic]
 impl AppState {
     #[app::init]
-    pub fn init() -> AppState {
-        AppState::default()
+    pub fn init(bootstrap: Option<Bootstrap>) -> app::Result<AppState> {
+        let mut state = AppState::default();
+
+        state.bootstrap(bootstrap.unwrap_or_default())?;
+
+        Ok(state)
     }
 }
@@ -0,0 +1,217 @@
+use calimero_sdk::app;
+use calimero_sdk::serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::error::{bail, ErrorCode};
+use crate::text::{self, TextKind};
+
+const ALLOWED_SCHEMES: &[&str] = &["https", "ipfs", "ipns"];
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("invalid link: {link}")]
+    InvalidLink { link: String },
+    #[error("link scheme not allowed: {scheme} (expected one of {allowed:?})")]
+    DisallowedScheme {
+        scheme: String,
+        allowed: &'static [&'static str],
+    },
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::InvalidLink { .. } => 8002,
+            Error::DisallowedScheme { .. } => 8003,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum LinkKind {
+    GithubPullRequest,
+    GithubIssue,
+    GithubRepository,
+    Ipfs,
+    Website,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct LinkView {
+    pub url: String,
+    pub kind: LinkKind,
+}
+
+impl From<String> for LinkView {
+    fn from(url: String) -> Self {
+        let kind = classify(&url);
+
+        Self { url, kind }
+    }
+}
+
+/// Cleans up user input the way [`text::normalize`] does any line, then
+/// returns its [canonical form](normalize_link). Links are both stored and
+/// looked up for removal through this.
+pub fn canonical_link(link: &str) -> app::Result<String> {
+    let link = text::normalize("link", link, TextKind::Line)?;
+
+    normalize_link(&link)
+}
+
+/// Parses `link`, checks its scheme against the allow-list and returns its
+/// canonical form, so equivalent links de-duplicate when stored in a set.
+///
+/// The scheme and, for `https`, the host are lower-cased, the default port,
+/// the fragment and trailing slashes are dropped. The query is kept as-is.
+pub fn normalize_link(link: &str) -> app::Result<String> {
+    let invalid = || Error::InvalidLink {
+        link: link.to_owned(),
+    };
+
+    // a URL never contains raw whitespace, it has to be percent-encoded
+    if link.contains(char::is_whitespace) {
+        bail!(invalid());
+    }
+
+    let Some((scheme, rest)) = link.split_once("://") else {
+        bail!(invalid());
+    };
+
+    let scheme = scheme.to_ascii_lowercase();
+
+    if !ALLOWED_SCHEMES.contains(&scheme.as_str()) {
+        bail!(Error::DisallowedScheme {
+            scheme,
+            allowed: ALLOWED_SCHEMES,
+        });
+    }
+
+    let rest = rest.split('#').next().unwrap_or_default();
+
+    let (authority, rest) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+
+    if authority.is_empty() || authority.contains('@') {
+        bail!(invalid());
+    }
+
+    let authority = match scheme.as_str() {
+        // content identifiers are case-sensitive
+        "ipfs" | "ipns" => authority.to_owned(),
+        _ => {
+            let authority = authority.to_ascii_lowercase();
+
+            match authority.strip_suffix(":443") {
+                Some(host) => host.to_owned(),
+                None => authority,
+            }
+        }
+    };
+
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    };
+
+    let path = path.trim_end_matches('/');
+
+    let mut normalized = format!("{scheme}://{authority}{path}");
+
+    if let Some(query) = query.filter(|q| !q.is_empty()) {
+        normalized.push('?');
+        normalized.push_str(query);
+    }
+
+    Ok(normalized)
+}
+
+/// Classifies a link previously returned by [`normalize_link`].
+pub fn classify(link: &str) -> LinkKind {
+    if link.starts_with("ipfs://") || link.starts_with("ipns://") {
+        return LinkKind::Ipfs;
+    }
+
+    let Some(path) = link.strip_prefix("https://github.com/") else {
+        return LinkKind::Website;
+    };
+
+    let path = path.split('?').next().unwrap_or_default();
+
+    let segments = path.split('/').collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        [_owner, _repo] => LinkKind::GithubRepository,
+        [_owner, _repo, "pull", number, ..] if number.parse::<u64>().is_ok() => {
+            LinkKind::GithubPullRequest
+        }
+        [_owner, _repo, "issues", number, ..] if number.parse::<u64>().is_ok() => {
+            LinkKind::GithubIssue
+        }
+        _ => LinkKind::Website,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_are_normalized() {
+        let link = normalize_link("HTTPS://GitHub.com:443/calimero-network/crumb/#readme").unwrap();
+
+        assert_eq!(link, "https://github.com/calimero-network/crumb");
+    }
+
+    #[test]
+    fn ipfs_identifiers_keep_their_case() {
+        let link = normalize_link("ipfs://QmYwAPJzv5CZsnA/report.pdf").unwrap();
+
+        assert_eq!(link, "ipfs://QmYwAPJzv5CZsnA/report.pdf");
+    }
+
+    #[test]
+    fn disallowed_schemes_are_rejected() {
+        assert!(normalize_link("http://example.com").is_err());
+        assert!(normalize_link("javascript://alert(1)").is_err());
+        assert!(normalize_link("example.com").is_err());
+        assert!(normalize_link("https://user@example.com").is_err());
+    }
+
+    #[test]
+    fn whitespace_is_rejected() {
+        assert!(normalize_link("https://example.com/my report.pdf").is_err());
+        assert!(normalize_link("https://example.com/a?q=one two").is_err());
+        assert!(normalize_link("ipfs://Qm\tYw").is_err());
+
+        let link = normalize_link("https://example.com/my%20report.pdf").unwrap();
+
+        assert_eq!(link, "https://example.com/my%20report.pdf");
+    }
+
+    #[test]
+    fn links_are_classified() {
+        let cases = [
+            ("https://github.com/a/b", LinkKind::GithubRepository),
+            (
+                "https://github.com/a/b/pull/12",
+                LinkKind::GithubPullRequest,
+            ),
+            (
+                "https://github.com/a/b/pull/12/files",
+                LinkKind::GithubPullRequest,
+            ),
+            ("https://github.com/a/b/issues/7", LinkKind::GithubIssue),
+            ("https://github.com/a", LinkKind::Website),
+            ("ipfs://QmYwAPJzv5CZsnA", LinkKind::Ipfs),
+            ("https://calimero.network", LinkKind::Website),
+        ];
+
+        for (link, kind) in cases {
+            assert_eq!(classify(link), kind, "{link}");
+        }
+    }
+}
@@ -1,35 +1,42 @@
+use core::fmt;
 use std::collections::BTreeMap;
 
+use calimero_sdk::app;
 use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use calimero_sdk::serde::{Deserialize, Serialize};
-use calimero_sdk::{app, env};
 use calimero_storage::collections::{UnorderedMap, UnorderedSet};
 use thiserror::Error;
 
+use crate::activity::ActivityKind;
 use crate::assignment::AssignmentId;
 use crate::bid::BidId;
 use crate::bounty::BountyId;
-use crate::types::id::{self, IdExt};
+use crate::config::AppConfig;
+use crate::env;
+use crate::error::{bail, storage, ErrorCode, InternalError, ItemResult};
+use crate::schema;
+use crate::text::{self, TextKind};
+use crate::types::id;
 use crate::user::{User, UserId};
-use crate::utils::{borsh_char, unique};
+use crate::utils::borsh_char;
 use crate::AppState;
 
-id::define!(pub MessageId<8, 12>);
-
-const MAX_MESSAGE_LENGTH: usize = 1000;
-
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "calimero_sdk::borsh")]
-pub struct Message {
-    pub author: UserId,
-    pub timestamp: u64,
-    pub target: MessageTarget,
-    pub content: String,
-    pub reactions: UnorderedSet<Reaction>,
-    pub comments: UnorderedSet<MessageId>,
+id::define!(pub MessageId<8, 12> as "msg");
+
+schema::versioned! {
+    #[derive(Debug)]
+    @version(1)
+    pub struct Message {
+        pub author: UserId,
+        pub timestamp: u64,
+        pub target: MessageTarget,
+        pub content: String,
+        pub reactions: UnorderedSet<Reaction>,
+        pub comments: UnorderedSet<MessageId>,
+    }
 }
 
-#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
 #[serde(crate = "calimero_sdk::serde")]
 pub enum MessageTarget {
@@ -40,6 +47,17 @@ pub enum MessageTarget {
     Message(MessageId),
 }
 
+impl fmt::Display for MessageTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageTarget::Bounty(id) => id.fmt(f),
+            MessageTarget::Bid(id) => id.fmt(f),
+            MessageTarget::Assignment(id) => id.fmt(f),
+            MessageTarget::Message(id) => id.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug, BorshDeserialize, BorshSerialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
 pub struct Reaction {
@@ -47,8 +65,8 @@ pub struct Reaction {
         serialize_with = "borsh_char::ser",
         deserialize_with = "borsh_char::de"
     )]
-    emoji: char,
-    users: UnorderedMap<UserId, u64>,
+    pub emoji: char,
+    pub users: UnorderedMap<UserId, u64>,
 }
 
 #[derive(Debug, Error, Serialize)]
@@ -65,6 +83,25 @@ pub enum Error {
     MessageTooLong { got: usize, max: usize },
 }
 
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::MessageNotFound(_) => 5001,
+            Error::ParentMessageNotFound(_) => 5002,
+            Error::MessageTargetNotFound(_) => 5003,
+            Error::MessageTooLong { .. } => 5004,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::MessageNotFound(id) | Error::ParentMessageNotFound(id) => Some(id.to_string()),
+            Error::MessageTargetNotFound(target) => Some(target.to_string()),
+            Error::MessageTooLong { .. } => None,
+        }
+    }
+}
+
 impl AppState {
     pub fn internal_post_message(
         &mut self,
@@ -73,7 +110,7 @@ impl AppState {
         target: MessageTarget,
         content: String,
     ) -> app::Result<MessageId> {
-        let message_id = unique(|| MessageId::random(), |id| self.messages.contains(id))?;
+        let message_id = self.ids.next(|id: &MessageId| self.messages.contains(id))?;
 
         let timestamp = env::time_now();
 
@@ -87,40 +124,44 @@ impl AppState {
         };
 
         if let MessageTarget::Message(parent_id) = message.target {
-            let Some(mut parent) = self.messages.get(&parent_id)? else {
-                app::bail!(Error::ParentMessageNotFound(parent_id));
+            let Some(mut parent) = self.messages.get(&parent_id).map_err(storage)? else {
+                bail!(Error::ParentMessageNotFound(parent_id));
             };
 
-            let _ignored = parent.comments.insert(message_id)?;
+            let _ignored = parent.comments.insert(message_id).map_err(storage)?;
 
-            let _ignored = self.messages.insert(parent_id, parent)?;
+            let _ignored = self.messages.insert(parent_id, parent).map_err(storage)?;
         }
 
-        let _ignored = user.messages.insert(message_id)?;
+        let _ignored = user.messages.insert(message_id).map_err(storage)?;
 
-        let _ignored = self.messages.insert(message_id, message)?;
+        let _ignored = self.messages.insert(message_id, message).map_err(storage)?;
 
         Ok(message_id)
     }
 
     pub fn internal_get_message(&self, message_id: MessageId) -> app::Result<Message> {
-        let Some(message) = self.messages.get(&message_id)? else {
-            app::bail!(Error::MessageNotFound(message_id));
+        let Some(message) = self.messages.get(&message_id).map_err(storage)? else {
+            bail!(Error::MessageNotFound(message_id));
         };
 
         Ok(message)
     }
 }
 
-fn validate_message(message: &str) -> app::Result<()> {
-    if message.len() > MAX_MESSAGE_LENGTH {
-        app::bail!(Error::MessageTooLong {
-            got: message.len(),
-            max: MAX_MESSAGE_LENGTH,
+fn validate_message(config: &AppConfig, message: &str) -> app::Result<String> {
+    let message = text::normalize("message", message, TextKind::Multiline)?;
+
+    let got = text::measure(&message, config.length_policy);
+
+    if got > config.max_message_length {
+        bail!(Error::MessageTooLong {
+            got,
+            max: config.max_message_length,
         });
     }
 
-    Ok(())
+    Ok(message)
 }
 
 #[app::logic]
@@ -134,17 +175,23 @@ impl AppState {
 
         let mut user = self.get_registered_user(&user_id)?;
 
-        validate_message(&content)?;
+        let content = validate_message(&self.config, &content)?;
 
         let parent = match target {
-            MessageTarget::Bounty(id) => self.bounties.get(&id)?.map(|b| b.message),
-            MessageTarget::Bid(id) => self.bids.get(&id)?.map(|b| b.message),
-            MessageTarget::Assignment(id) => self.assignments.get(&id)?.map(|b| b.message),
+            MessageTarget::Bounty(id) => {
+                self.bounties.get(&id).map_err(storage)?.map(|b| b.message)
+            }
+            MessageTarget::Bid(id) => self.bids.get(&id).map_err(storage)?.map(|b| b.message),
+            MessageTarget::Assignment(id) => self
+                .assignments
+                .get(&id)
+                .map_err(storage)?
+                .map(|b| b.message),
             MessageTarget::Message(id) => Some(id),
         };
 
         let Some(parent) = parent else {
-            app::bail!(Error::MessageTargetNotFound(target));
+            bail!(Error::MessageTargetNotFound(target));
         };
 
         let message_id = self.internal_post_message(
@@ -154,12 +201,55 @@ impl AppState {
             content,
         )?;
 
-        let _ignored = self.users.insert(user_id, user)?;
+        let _ignored = self.users.insert(user_id, user).map_err(storage)?;
+
+        let bounty_id = self.thread_bounty(&target)?;
+
+        self.record_activity(
+            bounty_id,
+            ActivityKind::Commented {
+                message: message_id,
+                target,
+            },
+        )?;
 
         Ok(message_id)
     }
 }
 
+impl AppState {
+    fn internal_message_view(
+        &self,
+        message_id: MessageId,
+        message: Message,
+    ) -> Result<MessageView, InternalError> {
+        let reactions = message
+            .reactions
+            .iter()
+            .map_err(storage)?
+            .map(|reaction| {
+                let emoji = reaction.emoji;
+
+                let users = reaction.users.entries().map_err(storage)?.collect();
+
+                Ok(ReactionView { emoji, users })
+            })
+            .collect::<Result<_, InternalError>>()?;
+
+        let comments = message.comments.iter().map_err(storage)?.collect();
+
+        Ok(MessageView {
+            id: message_id,
+            author: message.author,
+            timestamp: message.timestamp,
+            target: message.target,
+            content: message.content,
+            reactions,
+            comments,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(crate = "calimero_sdk::serde")]
 pub struct MessageView {
@@ -184,28 +274,23 @@ impl AppState {
     pub fn get_message(&self, message_id: MessageId) -> app::Result<MessageView> {
         let message = self.internal_get_message(message_id)?;
 
-        let reactions = message
-            .reactions
-            .iter()?
-            .map(|reaction| {
-                let emoji = reaction.emoji;
-
-                let users = reaction.users.entries()?.collect();
-
-                Ok(ReactionView { emoji, users })
-            })
-            .collect::<app::Result<_>>()?;
-
-        let comments = message.comments.iter()?.collect();
+        Ok(self.internal_message_view(message_id, message)?)
+    }
 
-        Ok(MessageView {
-            id: message_id,
-            author: message.author,
-            timestamp: message.timestamp,
-            target: message.target,
-            content: message.content,
-            reactions,
-            comments,
-        })
+    pub fn get_messages(
+        &self,
+        message_ids: Vec<MessageId>,
+    ) -> app::Result<Vec<ItemResult<MessageView, Error>>> {
+        let views = message_ids
+            .into_iter()
+            .map(|message_id| -> ItemResult<_, Error> {
+                let Some(message) = self.messages.get(&message_id).map_err(storage)? else {
+                    return Err(Error::MessageNotFound(message_id).into());
+                };
+
+                Ok(self.internal_message_view(message_id, message)?)
+            });
+
+        Ok(views.collect())
     }
 }
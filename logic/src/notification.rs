@@ -0,0 +1,440 @@
+//! Per-user notification inbox.
+//!
+//! Activity that concerns someone directly, like a bid on a bounty they bid
+//! on too or a reply anywhere in a thread they wrote in, is delivered to their
+//! inbox in shared state as it is recorded. Watchlists are private, so activity on watched bounties is
+//! instead picked out of the activity log when the inbox is read, and its
+//! read state is kept privately too. Mute preferences are applied on read.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use calimero_storage::collections::UnorderedMap;
+
+use crate::activity::{Activity, ActivityId, ActivityKey, ActivityKind, MAX_THREAD_DEPTH};
+use crate::bounty::BountyId;
+use crate::error::storage;
+use crate::message::{MessageId, MessageTarget};
+use crate::paging::paginate;
+use crate::preferences::NotificationKind;
+use crate::private::{EntryHandle, PrivateRootState, StateKey};
+use crate::schema;
+use crate::user::UserId;
+use crate::AppState;
+
+pub const NOTIFICATION_INBOX_CAPACITY: usize = 200;
+
+pub const NOTIFICATION_INBOX_SLACK: usize = 20;
+
+const WATCH_RECEIPTS_KEY: StateKey = StateKey::new(*b"NOTIFICATIONS");
+
+schema::versioned! {
+    #[derive(Debug)]
+    @version(1)
+    pub struct Notification {
+        pub actor: UserId,
+        pub bounty: Option<BountyId>,
+        pub kind: NotificationKind,
+        pub activity: ActivityKind,
+        pub timestamp: u64,
+        pub read: bool,
+    }
+}
+
+/// Adds to an inbox, keyed by the activity that raised the notification. Past
+/// [`NOTIFICATION_INBOX_CAPACITY`] plus [`NOTIFICATION_INBOX_SLACK`] entries it
+/// is trimmed back to the most recent [`NOTIFICATION_INBOX_CAPACITY`].
+fn push_notification(
+    inbox: &mut UnorderedMap<ActivityId, Notification>,
+    id: ActivityId,
+    notification: Notification,
+) -> app::Result<()> {
+    let _ignored = inbox.insert(id, notification).map_err(storage)?;
+
+    let len = inbox.len().map_err(storage)?;
+
+    if len <= NOTIFICATION_INBOX_CAPACITY + NOTIFICATION_INBOX_SLACK {
+        return Ok(());
+    }
+
+    let mut keys = inbox
+        .entries()
+        .map_err(storage)?
+        .map(|(id, notification)| (notification.timestamp, id))
+        .collect::<Vec<_>>();
+
+    keys.sort_unstable();
+
+    for (_, id) in keys.into_iter().take(len - NOTIFICATION_INBOX_CAPACITY) {
+        let _ignored = inbox.remove(&id).map_err(storage)?;
+    }
+
+    Ok(())
+}
+
+/// Which notifications from watched bounties have been read.
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+struct WatchReceipts {
+    read_through: Option<ActivityKey>,
+    read: BTreeSet<ActivityId>,
+}
+
+impl WatchReceipts {
+    fn handle() -> EntryHandle<Self> {
+        EntryHandle::new(WATCH_RECEIPTS_KEY)
+    }
+
+    fn is_read(&self, (timestamp, id): ActivityKey) -> bool {
+        self.read_through
+            .is_some_and(|through| (timestamp, id) <= through)
+            || self.read.contains(&id)
+    }
+}
+
+impl ActivityKind {
+    /// How the activity is presented in an inbox, if at all.
+    fn notification_kind(&self) -> Option<NotificationKind> {
+        let kind = match self {
+            ActivityKind::BountyCreated => return None,
+            ActivityKind::BountyEdited => NotificationKind::Edit,
+            ActivityKind::UserInvited { .. } => NotificationKind::Invitation,
+            ActivityKind::BidPlaced { .. }
+            | ActivityKind::BidCountered { .. }
+            | ActivityKind::CounterOfferAccepted { .. } => NotificationKind::Bid,
+            ActivityKind::AssignmentOffered { .. } => NotificationKind::Assignment,
+            ActivityKind::AssignmentAccepted { .. } | ActivityKind::AssignmentDeclined { .. } => {
+                NotificationKind::StatusChange
+            }
+            ActivityKind::Commented { .. } => NotificationKind::Comment,
+        };
+
+        Some(kind)
+    }
+}
+
+impl AppState {
+    /// Everyone an activity concerns directly, leaving out whoever caused it.
+    fn notification_recipients(&self, activity: &Activity) -> app::Result<BTreeSet<UserId>> {
+        let mut recipients = BTreeSet::new();
+
+        match &activity.kind {
+            ActivityKind::BountyCreated => {}
+            ActivityKind::UserInvited { user } => {
+                let _ignored = recipients.insert(*user);
+            }
+            ActivityKind::BidCountered { recipient, .. }
+            | ActivityKind::CounterOfferAccepted { recipient, .. } => {
+                let _ignored = recipients.insert(*recipient);
+            }
+            ActivityKind::AssignmentOffered { assignee, .. } => {
+                let _ignored = recipients.insert(*assignee);
+            }
+            ActivityKind::BountyEdited
+            | ActivityKind::BidPlaced { .. }
+            | ActivityKind::AssignmentAccepted { .. }
+            | ActivityKind::AssignmentDeclined { .. } => {
+                if let Some(bounty_id) = activity.bounty {
+                    self.bounty_participants(bounty_id, &mut recipients)?;
+                }
+            }
+            ActivityKind::Commented { message, .. } => {
+                self.thread_participants(*message, &mut recipients)?;
+            }
+        }
+
+        let _ignored = recipients.remove(&activity.actor);
+
+        Ok(recipients)
+    }
+
+    /// The bounty's author, everyone who bid on it and everyone assigned to it.
+    fn bounty_participants(
+        &self,
+        bounty_id: BountyId,
+        participants: &mut BTreeSet<UserId>,
+    ) -> app::Result<()> {
+        let Some(bounty) = self.bounties.get(&bounty_id).map_err(storage)? else {
+            return Ok(());
+        };
+
+        let _ignored = participants.insert(bounty.author);
+
+        for bid_id in bounty.bids.iter().map_err(storage)? {
+            if let Some(bid) = self.bids.get(&bid_id).map_err(storage)? {
+                let _ignored = participants.insert(bid.author);
+            }
+        }
+
+        for assignment_id in bounty.assignments.iter().map_err(storage)? {
+            if let Some(assignment) = self.assignments.get(&assignment_id).map_err(storage)? {
+                let _ignored = participants.insert(assignment.assignee);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Authors of the message's siblings and of every message above it, and
+    /// whoever owns what the thread hangs off.
+    fn thread_participants(
+        &self,
+        message_id: MessageId,
+        participants: &mut BTreeSet<UserId>,
+    ) -> app::Result<()> {
+        let Some(message) = self.messages.get(&message_id).map_err(storage)? else {
+            return Ok(());
+        };
+
+        let mut target = message.target;
+        let mut siblings = true;
+
+        for _ in 0..MAX_THREAD_DEPTH {
+            let owner = match target {
+                MessageTarget::Message(parent_id) => {
+                    let Some(parent) = self.messages.get(&parent_id).map_err(storage)? else {
+                        return Ok(());
+                    };
+
+                    let _ignored = participants.insert(parent.author);
+
+                    if mem::take(&mut siblings) {
+                        for sibling_id in parent.comments.iter().map_err(storage)? {
+                            if let Some(sibling) =
+                                self.messages.get(&sibling_id).map_err(storage)?
+                            {
+                                let _ignored = participants.insert(sibling.author);
+                            }
+                        }
+                    }
+
+                    target = parent.target;
+
+                    continue;
+                }
+                MessageTarget::Bounty(id) => {
+                    self.bounties.get(&id).map_err(storage)?.map(|b| b.author)
+                }
+                MessageTarget::Bid(id) => self.bids.get(&id).map_err(storage)?.map(|b| b.author),
+                MessageTarget::Assignment(id) => self
+                    .assignments
+                    .get(&id)
+                    .map_err(storage)?
+                    .map(|a| a.assignee),
+            };
+
+            participants.extend(owner);
+
+            break;
+        }
+
+        Ok(())
+    }
+
+    /// Delivers an activity to the inbox of everyone it concerns, never to
+    /// the user who caused it.
+    pub fn notify(&mut self, id: ActivityId, activity: &Activity) -> app::Result<()> {
+        let Some(kind) = activity.kind.notification_kind() else {
+            return Ok(());
+        };
+
+        for recipient in self.notification_recipients(activity)? {
+            let mut inbox = self
+                .notifications
+                .get(&recipient)
+                .map_err(storage)?
+                .unwrap_or_else(UnorderedMap::new);
+
+            push_notification(
+                &mut inbox,
+                id,
+                Notification {
+                    actor: activity.actor,
+                    bounty: activity.bounty,
+                    kind,
+                    activity: activity.kind.clone(),
+                    timestamp: activity.timestamp,
+                    read: false,
+                },
+            )?;
+
+            let _ignored = self
+                .notifications
+                .insert(recipient, inbox)
+                .map_err(storage)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct NotificationView {
+    pub id: ActivityId,
+    pub kind: NotificationKind,
+    pub actor: UserId,
+    pub bounty: Option<BountyId>,
+    pub activity: ActivityKind,
+    pub timestamp: u64,
+    /// Raised because the bounty is watched, rather than addressed to the user.
+    pub watched: bool,
+    pub read: bool,
+}
+
+#[app::logic]
+impl AppState {
+    /// Lists the current user's notifications newest first, leaving out
+    /// muted kinds and bounties.
+    pub fn list_notifications(
+        &self,
+        unread_only: Option<bool>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<NotificationView>> {
+        let user_id = self.current_user();
+
+        let root = PrivateRootState::load()?;
+
+        let mut views = BTreeMap::new();
+
+        if let Some(inbox) = self.notifications.get(&user_id).map_err(storage)? {
+            for (id, notification) in inbox.entries().map_err(storage)? {
+                let _ignored = views.insert(
+                    (notification.timestamp, id),
+                    NotificationView {
+                        id,
+                        kind: notification.kind,
+                        actor: notification.actor,
+                        bounty: notification.bounty,
+                        activity: notification.activity,
+                        timestamp: notification.timestamp,
+                        watched: false,
+                        read: notification.read,
+                    },
+                );
+            }
+        }
+
+        if !root.watchlist.is_empty() {
+            let receipts = WatchReceipts::handle().get()?;
+
+            for (id, activity) in self.activity.entries()? {
+                let Some(bounty_id) = activity.bounty else {
+                    continue;
+                };
+
+                if activity.actor == user_id || !root.watchlist.contains(&bounty_id) {
+                    continue;
+                }
+
+                let Some(kind) = activity.kind.notification_kind() else {
+                    continue;
+                };
+
+                let read = receipts
+                    .as_ref()
+                    .is_some_and(|receipts| receipts.is_read((activity.timestamp, id)));
+
+                let _ignored = views
+                    .entry((activity.timestamp, id))
+                    .or_insert(NotificationView {
+                        id,
+                        kind,
+                        actor: activity.actor,
+                        bounty: activity.bounty,
+                        activity: activity.kind,
+                        timestamp: activity.timestamp,
+                        watched: true,
+                        read,
+                    });
+            }
+        }
+
+        let preferences = &root.preferences;
+        let unread_only = unread_only.unwrap_or(false);
+
+        let views = views.into_values().rev().filter(|view| {
+            let muted = preferences.muted_kinds.contains(&view.kind)
+                || view
+                    .bounty
+                    .is_some_and(|bounty| preferences.muted_bounties.contains(&bounty));
+
+            !muted && !(unread_only && view.read)
+        });
+
+        Ok(paginate(views, offset, length))
+    }
+
+    pub fn mark_read(&mut self, ids: Vec<ActivityId>) -> app::Result<()> {
+        let user_id = self.current_user();
+
+        let mut receipts = WatchReceipts::handle().get_or_default()?;
+
+        let mut state = receipts.as_mut();
+
+        // anything that fell out of the log can't be listed anymore
+        for id in mem::take(&mut state.read) {
+            if self.activity.contains(&id)? {
+                let _ignored = state.read.insert(id);
+            }
+        }
+
+        let mut inbox = self
+            .notifications
+            .get(&user_id)
+            .map_err(storage)?
+            .unwrap_or_else(UnorderedMap::new);
+
+        for id in ids {
+            match inbox.get(&id).map_err(storage)? {
+                Some(mut notification) => {
+                    notification.read = true;
+
+                    let _ignored = inbox.insert(id, notification).map_err(storage)?;
+                }
+                None => {
+                    let _ignored = state.read.insert(id);
+                }
+            }
+        }
+
+        if inbox.len().map_err(storage)? > 0 {
+            let _ignored = self.notifications.insert(user_id, inbox).map_err(storage)?;
+        }
+
+        state.commit()
+    }
+
+    pub fn mark_all_read(&mut self) -> app::Result<()> {
+        let user_id = self.current_user();
+
+        if let Some(mut inbox) = self.notifications.get(&user_id).map_err(storage)? {
+            let unread = inbox
+                .entries()
+                .map_err(storage)?
+                .filter(|(_, notification)| !notification.read)
+                .collect::<Vec<_>>();
+
+            for (id, mut notification) in unread {
+                notification.read = true;
+
+                let _ignored = inbox.insert(id, notification).map_err(storage)?;
+            }
+
+            let _ignored = self.notifications.insert(user_id, inbox).map_err(storage)?;
+        }
+
+        let mut receipts = WatchReceipts::handle().get_or_default()?;
+
+        let mut state = receipts.as_mut();
+
+        state.read_through = self.activity.latest()?;
+        state.read.clear();
+
+        state.commit()
+    }
+}
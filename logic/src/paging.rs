@@ -2,12 +2,37 @@ use std::collections::BTreeMap;
 
 use calimero_sdk::app;
 use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::Serialize;
+use thiserror::Error;
 
+use crate::alloc::IdAllocator;
+use crate::error::{bail, ErrorCode};
 use crate::private::{EntryHandle, EntryRef, StateKey};
 use crate::types::id::{self, IdExt};
-use crate::utils::unique;
 
-id::define!(pub ResumptionToken<13, 18>);
+id::define!(pub ResumptionToken<17, 24> as "tok");
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("invalid resumption token: {0}")]
+    InvalidResumptionToken(ResumptionToken),
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::InvalidResumptionToken(_) => 10001,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::InvalidResumptionToken(token) => Some(token.to_string()),
+        }
+    }
+}
 
 #[derive(Default, BorshSerialize, BorshDeserialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
@@ -35,21 +60,32 @@ impl PagingSessions {
             }
         }
 
-        app::bail!("invalid resumption token")
+        bail!(Error::InvalidResumptionToken(token))
     }
 
-    pub fn new_session(&mut self) -> app::Result<EntryRef<PagingSession>> {
-        let token = unique(
-            || ResumptionToken::random(),
-            |t| self.sessions.contains_key(t),
-        )?;
+    pub fn new_session(&mut self, ids: &mut IdAllocator) -> app::Result<EntryRef<PagingSession>> {
+        let token = ids.next(|t: &ResumptionToken| self.sessions.contains_key(t))?;
 
-        let handle = EntryHandle::new(*token.transmute_ref());
-
-        let handle = self.sessions.entry(token).or_insert(handle);
+        let handle = self
+            .sessions
+            .entry(token)
+            .or_insert_with(|| EntryHandle::new(StateKey::random()));
 
         handle.get_or_init_with(|| PagingSession {
             items: EntryHandle::new(StateKey::random()),
         })
     }
 }
+
+pub fn paginate<T>(
+    items: impl IntoIterator<Item = T>,
+    offset: Option<usize>,
+    length: Option<usize>,
+) -> Vec<T> {
+    let items = items.into_iter().skip(offset.unwrap_or_default());
+
+    match length {
+        Some(length) => items.take(length).collect(),
+        None => items.collect(),
+    }
+}
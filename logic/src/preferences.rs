@@ -0,0 +1,65 @@
+use std::collections::BTreeSet;
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+
+use crate::bounty::BountyId;
+use crate::private::PrivateRootState;
+use crate::AppState;
+
+#[derive(
+    Eq,
+    Ord,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    PartialOrd,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum NotificationKind {
+    Bid,
+    Comment,
+    Invitation,
+    Assignment,
+    StatusChange,
+    Edit,
+}
+
+#[derive(Default, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct NotificationPreferences {
+    #[serde(default)]
+    pub muted_kinds: BTreeSet<NotificationKind>,
+    #[serde(default)]
+    pub muted_bounties: BTreeSet<BountyId>,
+}
+
+#[app::logic]
+impl AppState {
+    pub fn get_notification_preferences(&self) -> app::Result<NotificationPreferences> {
+        let root = PrivateRootState::load()?;
+
+        Ok(root.preferences.clone())
+    }
+
+    pub fn set_notification_preferences(
+        &mut self,
+        preferences: NotificationPreferences,
+    ) -> app::Result<()> {
+        let mut root = PrivateRootState::load()?;
+
+        let mut state = root.as_mut();
+
+        state.preferences = preferences;
+
+        state.commit()
+    }
+}
@@ -1,10 +1,16 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
+use calimero_sdk::app;
 use calimero_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use calimero_sdk::{app, env};
 
+use crate::bounty::BountyId;
+use crate::draft::Drafts;
+use crate::env;
+use crate::error::storage;
 use crate::paging::PagingSessions;
+use crate::preferences::NotificationPreferences;
 use crate::types::id;
 use crate::utils::IntoResult;
 
@@ -16,6 +22,15 @@ const PRIVATE_STATE_KEY: StateKey = StateKey::new(*b"PRIVATE_STATE");
 #[borsh(crate = "calimero_sdk::borsh")]
 pub struct PrivateRootState {
     paging_sessions: PagingSessions,
+    pub drafts: Drafts,
+    pub watchlist: BTreeSet<BountyId>,
+    pub preferences: NotificationPreferences,
+}
+
+impl PrivateRootState {
+    pub fn load() -> app::Result<EntryRef<Self>> {
+        EntryHandle::<Self>::root().get_or_default()
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -41,6 +56,12 @@ impl<T> EntryHandle<T> {
     }
 }
 
+impl<T> EntryHandle<T> {
+    pub fn delete(&self) -> app::Result<bool> {
+        Ok(env::storage_remove(self.key.as_ref()))
+    }
+}
+
 impl<T: BorshDeserialize> EntryHandle<T> {
     pub fn get(&self) -> app::Result<Option<EntryRef<T>>> {
         let key = self.key;
@@ -49,11 +70,13 @@ impl<T: BorshDeserialize> EntryHandle<T> {
             return Ok(None);
         };
 
-        let state = T::try_from_slice(&data)?;
+        let state = T::try_from_slice(&data).map_err(storage)?;
 
         Ok(Some(EntryRef { key, state }))
     }
 
+    /// Loads the entry, or starts from `f` in memory if there is none. Nothing
+    /// is written until the entry is committed, so views can load freely.
     pub fn get_or_init_with<R: IntoResult<T>>(
         &self,
         f: impl FnOnce() -> R,
@@ -61,17 +84,14 @@ impl<T: BorshDeserialize> EntryHandle<T> {
     where
         R::Error: core::error::Error,
     {
-        let key = self.key;
-
-        let Some(data) = env::storage_read(key.as_ref()) else {
-            let state = f().into_result()?;
-
-            return Ok(EntryRef { key, state });
-        };
-
-        let state = T::try_from_slice(&data)?;
+        if let Some(entry) = self.get()? {
+            return Ok(entry);
+        }
 
-        Ok(EntryRef { key, state })
+        Ok(EntryRef {
+            key: self.key,
+            state: f().into_result()?,
+        })
     }
 
     pub fn get_or_default(&self) -> app::Result<EntryRef<T>>
@@ -107,29 +127,183 @@ impl<T> Deref for EntryRef<T> {
     }
 }
 
+/// Changes are only persisted once [`EntryMut::commit`] is called,
+/// or the entry is staged in a [`Transaction`] that gets committed.
+#[must_use = "changes to an entry are lost unless committed"]
 pub struct EntryMut<'a, T: BorshSerialize> {
     key: StateKey,
     state: &'a mut T,
 }
 
+impl<T: BorshSerialize> EntryMut<'_, T> {
+    pub fn commit(self) -> app::Result<()> {
+        let mut transaction = Transaction::new();
+
+        transaction.stage(self)?;
+
+        transaction.commit()
+    }
+}
+
 impl<T: BorshSerialize> Deref for EntryMut<'_, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &self.state
+        self.state
     }
 }
 
 impl<T: BorshSerialize> DerefMut for EntryMut<'_, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.state
+        self.state
+    }
+}
+
+/// Batches writes and deletions of several entries.
+///
+/// Every entry is serialized as it is staged, so by the time [`Transaction::commit`]
+/// runs nothing can fail halfway and leave the store partially updated.
+#[derive(Default)]
+#[must_use = "staged changes are lost unless committed"]
+pub struct Transaction {
+    changes: BTreeMap<StateKey, Option<Vec<u8>>>,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stage<T: BorshSerialize>(&mut self, entry: EntryMut<'_, T>) -> app::Result<()> {
+        let data = borsh::to_vec(entry.state).map_err(storage)?;
+
+        let _ignored = self.changes.insert(entry.key, Some(data));
+
+        Ok(())
+    }
+
+    pub fn delete<T>(&mut self, handle: &EntryHandle<T>) {
+        let _ignored = self.changes.insert(handle.key, None);
+    }
+
+    pub fn commit(self) -> app::Result<()> {
+        for (key, data) in self.changes {
+            let _existed = match data {
+                Some(data) => env::storage_write(key.as_ref(), &data),
+                None => env::storage_remove(key.as_ref()),
+            };
+        }
+
+        Ok(())
     }
 }
 
-impl<T: BorshSerialize> Drop for EntryMut<'_, T> {
-    fn drop(&mut self) {
-        let data = borsh::to_vec(self.state).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::mock;
+
+    #[derive(Default, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+    #[borsh(crate = "calimero_sdk::borsh")]
+    struct Counter {
+        value: u64,
+    }
+
+    const KEY_A: StateKey = StateKey::new(*b"TEST_ENTRY__A");
+    const KEY_B: StateKey = StateKey::new(*b"TEST_ENTRY__B");
+
+    fn value_at(key: StateKey) -> Option<u64> {
+        let entry = EntryHandle::<Counter>::new(key).get().unwrap();
+
+        entry.map(|entry| entry.value)
+    }
+
+    #[test]
+    fn keyed_entry_commits_to_its_own_key() {
+        let mut entry = EntryHandle::<Counter>::new(KEY_A).get_or_default().unwrap();
+
+        let mut state = entry.as_mut();
+        state.value = 7;
+        state.commit().unwrap();
+
+        assert_eq!(value_at(KEY_A), Some(7));
+        assert_eq!(mock::storage_read(PRIVATE_STATE_KEY.as_ref()), None);
+    }
+
+    #[test]
+    fn get_or_init_with_only_persists_on_commit() {
+        let handle = EntryHandle::<Counter>::new(KEY_A);
+
+        let mut entry = handle.get_or_init_with(|| Counter { value: 3 }).unwrap();
+
+        assert_eq!(entry.value, 3);
+        assert_eq!(value_at(KEY_A), None);
+
+        entry.as_mut().commit().unwrap();
+
+        let entry = handle.get_or_init_with(|| Counter { value: 5 }).unwrap();
+
+        assert_eq!(entry.value, 3);
+    }
+
+    #[test]
+    fn loading_the_root_writes_nothing() {
+        let root = PrivateRootState::load().unwrap();
+
+        assert!(root.watchlist.is_empty());
+        assert!(mock::storage_keys().is_empty());
+    }
+
+    #[test]
+    fn uncommitted_changes_are_discarded() {
+        let mut entry = EntryHandle::<Counter>::new(KEY_A).get_or_default().unwrap();
+
+        let mut state = entry.as_mut();
+        state.value = 9;
+        drop(state);
+
+        assert_eq!(value_at(KEY_A), None);
+    }
+
+    #[test]
+    fn delete_removes_the_entry() {
+        let handle = EntryHandle::<Counter>::new(KEY_A);
+
+        let mut entry = handle.get_or_default().unwrap();
+
+        entry.as_mut().commit().unwrap();
+
+        assert!(handle.delete().unwrap());
+        assert!(!handle.delete().unwrap());
+        assert_eq!(value_at(KEY_A), None);
+    }
+
+    #[test]
+    fn transaction_applies_all_staged_changes_on_commit() {
+        let handle_a = EntryHandle::<Counter>::new(KEY_A);
+        let handle_b = EntryHandle::<Counter>::new(KEY_B);
+
+        let mut entry_a = handle_a.get_or_default().unwrap();
+        let mut entry_b = handle_b.get_or_default().unwrap();
+
+        entry_a.as_mut().commit().unwrap();
+        entry_b.as_mut().commit().unwrap();
+
+        let mut transaction = Transaction::new();
+
+        let mut state_a = entry_a.as_mut();
+        state_a.value = 42;
+        transaction.stage(state_a).unwrap();
+
+        transaction.delete(&handle_b);
+
+        assert_eq!(value_at(KEY_A), Some(0));
+        assert_eq!(value_at(KEY_B), Some(0));
+
+        transaction.commit().unwrap();
 
-        let _ignored = env::storage_write(PRIVATE_STATE_KEY.as_ref(), &data);
+        assert_eq!(value_at(KEY_A), Some(42));
+        assert_eq!(value_at(KEY_B), None);
+        assert_eq!(mock::storage_keys(), vec![KEY_A.as_ref().to_vec()]);
     }
 }
@@ -0,0 +1,541 @@
+//! Versioned storage layouts.
+//!
+//! The app state and every stored entity are declared through [`versioned!`],
+//! which prefixes their Borsh encoding with [`TAG`] and a layout version:
+//!
+//! ```text
+//! 0xFF 'c' 'r' 'b' | version: u8 | fields...
+//! ```
+//!
+//! Records written before versioning was introduced carry no tag and are
+//! decoded as version 1. Older records are upgraded lazily when read and
+//! written back in the current layout on the next write, or eagerly in
+//! batches through [`AppState::migrate_records`].
+//!
+//! Adding a field is a matter of bumping `@version` and marking the field
+//! `@since(n)`, records older than `n` get its `Default`. Anything else
+//! needs an `@upgrade(path)` hook, which decodes records older than the
+//! current version by hand.
+
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::BorshDeserialize;
+use calimero_sdk::serde::{Deserialize, Serialize};
+
+use crate::activity::Activity;
+use crate::assignment::Assignment;
+use crate::bid::Bid;
+use crate::bounty::Bounty;
+use crate::error::storage;
+use crate::label::Label;
+use crate::message::Message;
+use crate::notification::Notification;
+use crate::user::{User, UserEdit};
+use crate::AppState;
+
+/// Marks a versioned record. Untagged layouts start with an enum or option
+/// tag, or with random ID bytes, so they are practically never mistaken for it.
+pub const TAG: [u8; 4] = [0xFF, b'c', b'r', b'b'];
+
+pub trait Versioned: Sized {
+    const VERSION: u8;
+
+    /// Decodes a record laid out as of `version`, never newer than [`Self::VERSION`].
+    fn decode<R: Read>(version: u8, reader: &mut R) -> io::Result<Self>;
+}
+
+pub fn read<T: Versioned, R: Read>(reader: &mut R) -> io::Result<T> {
+    let mut tag = [0; TAG.len()];
+
+    reader.read_exact(&mut tag)?;
+
+    if tag != TAG {
+        // written before versioning, which is what version 1 lays out
+        return T::decode(1, &mut tag.as_slice().chain(reader));
+    }
+
+    let version = u8::deserialize_reader(reader)?;
+
+    if version > T::VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} record is from a newer schema (v{version} > v{})",
+                std::any::type_name::<T>(),
+                T::VERSION
+            ),
+        ));
+    }
+
+    T::decode(version, reader)
+}
+
+macro_rules! versioned {
+    (
+        $(#[$meta:meta])*
+        @version($version:literal)
+        $(@upgrade($upgrade:path))?
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $(@since($since:literal))?
+                $field_vis:vis $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $crate::schema::Versioned for $name {
+            const VERSION: u8 = $version;
+
+            fn decode<R: ::std::io::Read>(version: u8, reader: &mut R) -> ::std::io::Result<Self> {
+                $(
+                    if version < $version {
+                        return $upgrade(version, reader as &mut dyn ::std::io::Read);
+                    }
+                )?
+
+                let _ = version;
+
+                Ok(Self {
+                    $(
+                        $field: $crate::schema::versioned!(@field reader, version $(, $since)?),
+                    )*
+                })
+            }
+        }
+
+        impl ::calimero_sdk::borsh::BorshSerialize for $name {
+            fn serialize<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+                writer.write_all(&$crate::schema::TAG)?;
+                writer.write_all(&[$version])?;
+
+                $(
+                    ::calimero_sdk::borsh::BorshSerialize::serialize(&self.$field, writer)?;
+                )*
+
+                Ok(())
+            }
+        }
+
+        impl ::calimero_sdk::borsh::BorshDeserialize for $name {
+            fn deserialize_reader<R: ::std::io::Read>(reader: &mut R) -> ::std::io::Result<Self> {
+                $crate::schema::read(reader)
+            }
+        }
+    };
+    (@field $reader:ident, $version:ident) => {
+        ::calimero_sdk::borsh::BorshDeserialize::deserialize_reader($reader)?
+    };
+    (@field $reader:ident, $version:ident, $since:literal) => {
+        if $version >= $since {
+            ::calimero_sdk::borsh::BorshDeserialize::deserialize_reader($reader)?
+        } else {
+            ::core::default::Default::default()
+        }
+    };
+}
+
+pub(crate) use versioned;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum RecordKind {
+    Users,
+    UserEdits,
+    Bounties,
+    Bids,
+    Assignments,
+    Messages,
+    Labels,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct SchemaInfo {
+    pub state: u8,
+    pub records: BTreeMap<String, u8>,
+}
+
+macro_rules! rewrite {
+    ($map:expr, $offset:expr, $length:expr) => {{
+        let records = $map
+            .entries()
+            .map_err(storage)?
+            .skip($offset)
+            .take($length)
+            .collect::<Vec<_>>();
+
+        let count = records.len();
+
+        for (id, record) in records {
+            let _ignored = $map.insert(id, record).map_err(storage)?;
+        }
+
+        count
+    }};
+}
+
+#[app::logic]
+impl AppState {
+    pub fn get_schema_info(&self) -> app::Result<SchemaInfo> {
+        let records = [
+            ("User", User::VERSION),
+            ("UserEdit", UserEdit::VERSION),
+            ("Bounty", Bounty::VERSION),
+            ("Bid", Bid::VERSION),
+            ("Assignment", Assignment::VERSION),
+            ("Message", Message::VERSION),
+            ("Label", Label::VERSION),
+            ("Activity", Activity::VERSION),
+            ("Notification", Notification::VERSION),
+        ];
+
+        Ok(SchemaInfo {
+            state: AppState::VERSION,
+            records: records
+                .into_iter()
+                .map(|(kind, version)| (kind.to_owned(), version))
+                .collect(),
+        })
+    }
+
+    /// Rewrites a page of records in the current layout, returning how many
+    /// were visited, so admins can migrate a context ahead of reads. The
+    /// state itself is rewritten on every call.
+    pub fn migrate_records(
+        &mut self,
+        kind: RecordKind,
+        offset: usize,
+        length: usize,
+    ) -> app::Result<usize> {
+        self.ensure_admin(&self.current_user())?;
+
+        let count = match kind {
+            RecordKind::Users => rewrite!(self.users, offset, length),
+            RecordKind::UserEdits => rewrite!(self.user_edits, offset, length),
+            RecordKind::Bounties => rewrite!(self.bounties, offset, length),
+            RecordKind::Bids => rewrite!(self.bids, offset, length),
+            RecordKind::Assignments => rewrite!(self.assignments, offset, length),
+            RecordKind::Messages => rewrite!(self.messages, offset, length),
+            RecordKind::Labels => rewrite!(self.labels, offset, length),
+        };
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use calimero_sdk::borsh::{self, BorshSerialize};
+    use calimero_storage::collections::{UnorderedMap, UnorderedSet};
+
+    use super::*;
+    use crate::assignment::AssignmentId;
+    use crate::bid::{BidId, BidStatus};
+    use crate::bounty::{BountyId, BountyStatus};
+    use crate::label::LabelId;
+    use crate::message::MessageId;
+    use crate::testing::{user, Harness};
+    use crate::user::{UserId, UserRemarks};
+
+    // Layouts as of the last release before versioning, copied verbatim, so
+    // these tests decode exactly what deployed contexts have in storage.
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    #[borsh(crate = "calimero_sdk::borsh")]
+    struct BaselineUser {
+        name: Option<String>,
+        skills: UnorderedSet<String>,
+        links: UnorderedSet<String>,
+
+        total_reward: u128,
+
+        bids: UnorderedSet<BidId>,
+        assignments: UnorderedSet<BidId>,
+        bounties: UnorderedSet<BountyId>,
+
+        messages: UnorderedSet<MessageId>,
+        remarks: UnorderedSet<UserRemarks>,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    #[borsh(crate = "calimero_sdk::borsh")]
+    struct BaselineBounty {
+        title: String,
+        author: UserId,
+        message: MessageId,
+
+        award: Option<u128>,
+        status: BountyStatus,
+        is_epic: bool,
+        deadline: Option<u64>,
+
+        labels: UnorderedSet<LabelId>,
+        reviewers: UnorderedSet<UserId>,
+
+        bids: UnorderedSet<BidId>,
+        assignments: UnorderedSet<AssignmentId>,
+
+        parent: Option<BountyId>,
+        children: UnorderedSet<BountyId>,
+
+        triaged_by: Option<UserId>,
+        approved_by: Option<UserId>,
+        closed_by: Option<UserId>,
+
+        proposed_at: Option<u64>,
+        triaged_at: Option<u64>,
+        approved_at: Option<u64>,
+        closed_at: Option<u64>,
+        updated_at: Option<u64>,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    #[borsh(crate = "calimero_sdk::borsh")]
+    struct BaselineBid {
+        author: UserId,
+        bounty: BountyId,
+
+        message: MessageId,
+        assignment: Option<AssignmentId>,
+
+        status: BidStatus,
+        expiry: Option<u64>,
+        reward: UnorderedMap<String, u128>,
+        duration: Option<u64>,
+
+        proposed_at: Option<u64>,
+        approved_at: Option<u64>,
+        retracted_at: Option<u64>,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    #[borsh(crate = "calimero_sdk::borsh")]
+    struct BaselineAppState {
+        users: UnorderedMap<UserId, BaselineUser>,
+        bids: UnorderedMap<BidId, BaselineBid>,
+        assignments: UnorderedMap<AssignmentId, Assignment>,
+        bounties: UnorderedMap<BountyId, BaselineBounty>,
+        messages: UnorderedMap<MessageId, Message>,
+        labels: UnorderedMap<LabelId, Label>,
+    }
+
+    const BOUNTY: BountyId = BountyId::new(*b"bounty!!");
+    const BID: BidId = BidId::new(*b"bid!!!!!");
+    const MESSAGE: MessageId = MessageId::new(*b"message!");
+
+    fn baseline_user() -> BaselineUser {
+        let mut user = BaselineUser {
+            name: Some("alice".to_owned()),
+            skills: UnorderedSet::new(),
+            links: UnorderedSet::new(),
+            total_reward: 250,
+            bids: UnorderedSet::new(),
+            assignments: UnorderedSet::new(),
+            bounties: UnorderedSet::new(),
+            messages: UnorderedSet::new(),
+            remarks: UnorderedSet::new(),
+        };
+
+        let _ignored = user.skills.insert("rust".to_owned()).unwrap();
+        let _ignored = user.bids.insert(BID).unwrap();
+        let _ignored = user.bounties.insert(BOUNTY).unwrap();
+
+        user
+    }
+
+    fn baseline_bounty(author: UserId) -> BaselineBounty {
+        let mut bounty = BaselineBounty {
+            title: "fix the docs".to_owned(),
+            author,
+            message: MESSAGE,
+            award: Some(100),
+            status: BountyStatus::Triaged,
+            is_epic: false,
+            deadline: Some(42),
+            labels: UnorderedSet::new(),
+            reviewers: UnorderedSet::new(),
+            bids: UnorderedSet::new(),
+            assignments: UnorderedSet::new(),
+            parent: None,
+            children: UnorderedSet::new(),
+            triaged_by: Some(author),
+            approved_by: None,
+            closed_by: None,
+            proposed_at: Some(1),
+            triaged_at: Some(2),
+            approved_at: None,
+            closed_at: None,
+            updated_at: Some(3),
+        };
+
+        let _ignored = bounty.bids.insert(BID).unwrap();
+
+        bounty
+    }
+
+    fn baseline_bid(author: UserId) -> BaselineBid {
+        let mut bid = BaselineBid {
+            author,
+            bounty: BOUNTY,
+            message: MESSAGE,
+            assignment: None,
+            status: BidStatus::Proposed,
+            expiry: Some(7),
+            reward: UnorderedMap::new(),
+            duration: Some(50),
+            proposed_at: Some(4),
+            approved_at: None,
+            retracted_at: None,
+        };
+
+        let _ignored = bid.reward.insert("near".to_owned(), 100).unwrap();
+
+        bid
+    }
+
+    #[test]
+    fn baseline_users_decode() {
+        let _harness = Harness::new(user(1));
+
+        let bytes = borsh::to_vec(&baseline_user()).unwrap();
+
+        let decoded = borsh::from_slice::<User>(&bytes).unwrap();
+
+        assert_eq!(decoded.name.as_deref(), Some("alice"));
+        assert_eq!(decoded.total_reward, 250);
+        assert!(decoded.skills.contains(&"rust".to_owned()).unwrap());
+        assert!(decoded.bids.contains(&BID).unwrap());
+        assert!(decoded.bounties.contains(&BOUNTY).unwrap());
+        assert_eq!(decoded.assignments.len().unwrap(), 0);
+        assert_eq!(decoded.invitations.len().unwrap(), 0);
+        assert_eq!(decoded.edits.len().unwrap(), 0);
+
+        let bytes = borsh::to_vec(&decoded).unwrap();
+
+        assert_eq!(bytes[..5], [0xFF, b'c', b'r', b'b', User::VERSION]);
+        assert_eq!(borsh::from_slice::<User>(&bytes).unwrap().total_reward, 250);
+    }
+
+    #[test]
+    fn baseline_bounties_decode() {
+        let _harness = Harness::new(user(1));
+
+        let bytes = borsh::to_vec(&baseline_bounty(user(2))).unwrap();
+
+        let decoded = borsh::from_slice::<Bounty>(&bytes).unwrap();
+
+        assert_eq!(decoded.title, "fix the docs");
+        assert_eq!(decoded.author, user(2));
+        assert_eq!(decoded.message, MESSAGE);
+        assert_eq!(decoded.award, Some(100));
+        assert!(matches!(decoded.status, BountyStatus::Triaged));
+        assert_eq!(decoded.deadline, Some(42));
+        assert!(decoded.bids.contains(&BID).unwrap());
+        assert_eq!(decoded.invitations.len().unwrap(), 0);
+        assert_eq!(decoded.triaged_by, Some(user(2)));
+        assert_eq!(decoded.triaged_at, Some(2));
+        assert_eq!(decoded.updated_at, Some(3));
+    }
+
+    #[test]
+    fn baseline_bids_decode() {
+        let _harness = Harness::new(user(1));
+
+        let bytes = borsh::to_vec(&baseline_bid(user(2))).unwrap();
+
+        let decoded = borsh::from_slice::<Bid>(&bytes).unwrap();
+
+        assert_eq!(decoded.author, user(2));
+        assert_eq!(decoded.bounty, BOUNTY);
+        assert!(matches!(decoded.status, BidStatus::Proposed));
+        assert!(!decoded.invited);
+        assert_eq!(decoded.expiry, Some(7));
+        assert_eq!(decoded.reward.get(&"near".to_owned()).unwrap(), Some(100));
+        assert_eq!(decoded.duration, Some(50));
+        assert_eq!(decoded.proposed_at, Some(4));
+        assert!(decoded.negotiation.is_empty());
+    }
+
+    #[test]
+    fn baseline_app_state_decodes() {
+        let (alice, bob) = (user(2), user(3));
+        let _harness = Harness::new(user(1));
+
+        let mut baseline = BaselineAppState {
+            users: UnorderedMap::new(),
+            bids: UnorderedMap::new(),
+            assignments: UnorderedMap::new(),
+            bounties: UnorderedMap::new(),
+            messages: UnorderedMap::new(),
+            labels: UnorderedMap::new(),
+        };
+
+        let _ignored = baseline.users.insert(alice, baseline_user()).unwrap();
+        let _ignored = baseline
+            .bounties
+            .insert(BOUNTY, baseline_bounty(alice))
+            .unwrap();
+        let _ignored = baseline.bids.insert(BID, baseline_bid(bob)).unwrap();
+
+        let bytes = borsh::to_vec(&baseline).unwrap();
+
+        let state = borsh::from_slice::<AppState>(&bytes).unwrap();
+
+        let user = state.users.get(&alice).unwrap().unwrap();
+
+        assert_eq!(user.name.as_deref(), Some("alice"));
+        assert_eq!(state.bounties.get(&BOUNTY).unwrap().unwrap().author, alice);
+        assert_eq!(state.bids.get(&BID).unwrap().unwrap().author, bob);
+        assert_eq!(state.assignments.len().unwrap(), 0);
+        assert_eq!(state.admins.len().unwrap(), 0);
+        assert_eq!(state.user_edits.len().unwrap(), 0);
+        assert_eq!(state.context.name, None);
+
+        let bytes = borsh::to_vec(&state).unwrap();
+
+        assert_eq!(bytes[..5], [0xFF, b'c', b'r', b'b', AppState::VERSION]);
+    }
+
+    #[test]
+    fn newer_records_are_rejected() {
+        let fixture = [
+            0xFF,
+            b'c',
+            b'r',
+            b'b',
+            Label::VERSION + 1,
+            3,
+            0,
+            0,
+            0,
+            b'b',
+            b'u',
+            b'g',
+        ];
+
+        assert!(borsh::from_slice::<Label>(&fixture).is_err());
+    }
+
+    #[test]
+    fn untagged_labels_decode() {
+        let fixture = [3, 0, 0, 0, b'b', b'u', b'g'];
+
+        let label = borsh::from_slice::<Label>(&fixture).unwrap();
+
+        assert_eq!(label.name, "bug");
+        assert_eq!(
+            borsh::to_vec(&label).unwrap()[..5],
+            [0xFF, b'c', b'r', b'b', 1]
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Full-text search over bounty titles and descriptions.
+//!
+//! An inverted index maps every term to the bounties it appears in, weighted
+//! by how often, with title occurrences counting more. It's updated whenever
+//! a bounty's title or description message changes.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use calimero_sdk::app;
+use calimero_sdk::serde::{Deserialize, Serialize};
+use calimero_storage::collections::UnorderedMap;
+
+use crate::bounty::{BountyFilter, BountyId, BountyViewBrief};
+use crate::error::storage;
+use crate::paging::paginate;
+use crate::AppState;
+
+const TITLE_WEIGHT: u32 = 3;
+const MIN_TERM_LENGTH: usize = 2;
+const MAX_TERM_LENGTH: usize = 32;
+const SNIPPET_CONTEXT: usize = 6;
+
+const STOP_WORDS: &[&str] = &[
+    "an", "and", "are", "as", "at", "be", "by", "for", "in", "is", "it", "of", "on", "or", "the",
+    "to", "with",
+];
+
+/// Splits `text` into lower-cased alphanumeric terms, dropping stop words.
+pub fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|term| {
+            let len = term.chars().count();
+
+            (MIN_TERM_LENGTH..=MAX_TERM_LENGTH).contains(&len)
+                && !STOP_WORDS.contains(&term.as_str())
+        })
+}
+
+fn weights(title: &str, description: &str) -> BTreeMap<String, u32> {
+    let mut weights = BTreeMap::new();
+
+    for term in terms(title) {
+        *weights.entry(term).or_default() += TITLE_WEIGHT;
+    }
+
+    for term in terms(description) {
+        *weights.entry(term).or_default() += 1;
+    }
+
+    weights
+}
+
+/// Picks the words around the first one matching any of `query`.
+fn snippet(text: &str, query: &BTreeSet<String>) -> Option<String> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+
+    let hit = words
+        .iter()
+        .position(|word| terms(word).any(|term| query.contains(&term)))?;
+
+    let start = hit.saturating_sub(SNIPPET_CONTEXT);
+    let end = (hit + SNIPPET_CONTEXT + 1).min(words.len());
+
+    let mut snippet = words[start..end].join(" ");
+
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+
+    if end < words.len() {
+        snippet.push('…');
+    }
+
+    Some(snippet)
+}
+
+impl AppState {
+    fn bounty_text(&self, bounty_id: BountyId) -> app::Result<Option<(String, String)>> {
+        let Some(bounty) = self.bounties.get(&bounty_id).map_err(storage)? else {
+            return Ok(None);
+        };
+
+        let description = self
+            .messages
+            .get(&bounty.message)
+            .map_err(storage)?
+            .map(|message| message.content)
+            .unwrap_or_default();
+
+        Ok(Some((bounty.title, description)))
+    }
+
+    /// Adds the bounty's current title and description to the index.
+    pub fn index_bounty(&mut self, bounty_id: BountyId) -> app::Result<()> {
+        let Some((title, description)) = self.bounty_text(bounty_id)? else {
+            return Ok(());
+        };
+
+        for (term, weight) in weights(&title, &description) {
+            let mut postings = self
+                .search
+                .get(&term)
+                .map_err(storage)?
+                .unwrap_or_else(UnorderedMap::new);
+
+            let current = postings
+                .get(&bounty_id)
+                .map_err(storage)?
+                .unwrap_or_default();
+
+            let _ignored = postings
+                .insert(bounty_id, current + weight)
+                .map_err(storage)?;
+
+            let _ignored = self.search.insert(term, postings).map_err(storage)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the bounty's current title and description from the index,
+    /// call it before either changes.
+    pub fn unindex_bounty(&mut self, bounty_id: BountyId) -> app::Result<()> {
+        let Some((title, description)) = self.bounty_text(bounty_id)? else {
+            return Ok(());
+        };
+
+        for (term, weight) in weights(&title, &description) {
+            let Some(mut postings) = self.search.get(&term).map_err(storage)? else {
+                continue;
+            };
+
+            let current = postings
+                .get(&bounty_id)
+                .map_err(storage)?
+                .unwrap_or_default();
+
+            if current > weight {
+                let _ignored = postings
+                    .insert(bounty_id, current - weight)
+                    .map_err(storage)?;
+            } else {
+                let _ignored = postings.remove(&bounty_id).map_err(storage)?;
+            }
+
+            if postings.len().map_err(storage)? == 0 {
+                let _ignored = self.search.remove(&term).map_err(storage)?;
+            } else {
+                let _ignored = self.search.insert(term, postings).map_err(storage)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BountySearchHit {
+    pub bounty: BountyViewBrief,
+    pub score: u32,
+    pub snippets: Vec<String>,
+}
+
+#[app::logic]
+impl AppState {
+    /// Ranks bounties by how many of the query's terms they match, then by
+    /// how often they match them.
+    pub fn search_bounties(
+        &self,
+        query: String,
+        filter: Option<BountyFilter>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<BountySearchHit>> {
+        let query = terms(&query).collect::<BTreeSet<_>>();
+
+        let mut scores = BTreeMap::<BountyId, (usize, u32)>::new();
+
+        for term in &query {
+            let Some(postings) = self.search.get(term).map_err(storage)? else {
+                continue;
+            };
+
+            for (bounty_id, weight) in postings.entries().map_err(storage)? {
+                let (matched, score) = scores.entry(bounty_id).or_default();
+
+                *matched += 1;
+                *score += weight;
+            }
+        }
+
+        let mut ranked = Vec::with_capacity(scores.len());
+
+        for (bounty_id, (matched, score)) in scores {
+            if let Some(filter) = &filter {
+                let bounty = self.internal_get_bounty(bounty_id)?;
+
+                if !filter.matches(&bounty) {
+                    continue;
+                }
+            }
+
+            ranked.push((Reverse(matched), Reverse(score), bounty_id));
+        }
+
+        ranked.sort();
+
+        paginate(ranked, offset, length)
+            .into_iter()
+            .map(|(_, Reverse(score), bounty_id)| {
+                let (title, description) = self.bounty_text(bounty_id)?.unwrap_or_default();
+
+                let snippets = [title, description]
+                    .iter()
+                    .filter_map(|text| snippet(text, &query))
+                    .collect();
+
+                Ok(BountySearchHit {
+                    bounty: self.get_bounty_brief(bounty_id)?,
+                    score,
+                    snippets,
+                })
+            })
+            .collect()
+    }
+
+    /// Rebuilds the index for a page of bounties, for contexts that predate
+    /// search or after an import.
+    pub fn reindex_bounties(&mut self, offset: usize, length: usize) -> app::Result<usize> {
+        self.ensure_admin(&self.current_user())?;
+
+        let bounties = self
+            .bounties
+            .entries()
+            .map_err(storage)?
+            .skip(offset)
+            .take(length)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+
+        for bounty_id in &bounties {
+            // indexing is additive, so clear whatever is there first
+            self.unindex_bounty(*bounty_id)?;
+            self.index_bounty(*bounty_id)?;
+        }
+
+        Ok(bounties.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms_skip_stop_words_and_punctuation() {
+        let terms = terms("Fix the README, and CI!").collect::<Vec<_>>();
+
+        assert_eq!(terms, ["fix", "readme", "ci"]);
+    }
+
+    #[test]
+    fn snippets_surround_the_first_hit() {
+        let query = BTreeSet::from(["wasm".to_owned()]);
+
+        let text = "one two three four five six seven wasm eight nine ten eleven twelve thirteen";
+
+        let snippet = snippet(text, &query).unwrap();
+
+        assert_eq!(
+            snippet,
+            "…two three four five six seven wasm eight nine ten eleven twelve thirteen"
+        );
+    }
+}
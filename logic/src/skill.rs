@@ -0,0 +1,163 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use calimero_sdk::app;
+use calimero_storage::collections::UnorderedSet;
+
+use crate::error::storage;
+use crate::paging::paginate;
+use crate::text::{self, TextKind};
+use crate::user::{validate_skill, UserId, UserViewBrief};
+use crate::AppState;
+
+/// The one normalisation skills go through, both when they are stored and
+/// when they are looked up or removed.
+pub fn normalize_skill(skill: &str) -> app::Result<String> {
+    let skill = text::normalize("skill", skill, TextKind::Line)?;
+
+    Ok(skill.to_lowercase())
+}
+
+impl AppState {
+    pub fn index_user_skill(&mut self, user_id: UserId, skill: String) -> app::Result<()> {
+        let mut users = self
+            .skills
+            .get(&skill)
+            .map_err(storage)?
+            .unwrap_or_else(UnorderedSet::new);
+
+        let _ignored = users.insert(user_id).map_err(storage)?;
+
+        let _ignored = self.skills.insert(skill, users).map_err(storage)?;
+
+        Ok(())
+    }
+
+    pub fn unindex_user_skill(&mut self, user_id: UserId, skill: String) -> app::Result<()> {
+        let Some(mut users) = self.skills.get(&skill).map_err(storage)? else {
+            return Ok(());
+        };
+
+        let _ignored = users.remove(&user_id).map_err(storage)?;
+
+        if users.len().map_err(storage)? == 0 {
+            let _ignored = self.skills.remove(&skill).map_err(storage)?;
+        } else {
+            let _ignored = self.skills.insert(skill, users).map_err(storage)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[app::logic]
+impl AppState {
+    pub fn search_users(
+        &self,
+        query: Option<String>,
+        skills: Vec<String>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<UserViewBrief>> {
+        let query = query.map(|q| q.trim().to_lowercase());
+        let query = query.filter(|q| !q.is_empty());
+
+        let skills = skills
+            .iter()
+            .map(|s| normalize_skill(s))
+            .collect::<app::Result<BTreeSet<_>>>()?;
+
+        let skills = skills
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<BTreeSet<_>>();
+
+        let mut candidates = BTreeMap::<UserId, usize>::new();
+
+        if skills.is_empty() {
+            for (user_id, _) in self.users.entries().map_err(storage)? {
+                let _ignored = candidates.insert(user_id, 0);
+            }
+        } else {
+            for skill in skills {
+                let Some(users) = self.skills.get(&skill).map_err(storage)? else {
+                    continue;
+                };
+
+                for user_id in users.iter().map_err(storage)? {
+                    *candidates.entry(user_id).or_default() += 1;
+                }
+            }
+        }
+
+        let mut matches = candidates
+            .into_iter()
+            .map(|(user_id, overlap)| {
+                let Some(user) = self.users.get(&user_id).map_err(storage)? else {
+                    return Ok(None);
+                };
+
+                if let Some(query) = &query {
+                    let name = user.name.as_deref().unwrap_or_default().to_lowercase();
+
+                    if !name.contains(query)
+                        && !user
+                            .skills
+                            .iter()
+                            .map_err(storage)?
+                            .any(|s| s.contains(query))
+                    {
+                        return Ok(None);
+                    }
+                }
+
+                Ok(Some((overlap, user_id, user)))
+            })
+            .filter_map(|e| e.transpose())
+            .collect::<app::Result<Vec<_>>>()?;
+
+        matches.sort_by_key(|(overlap, ..)| Reverse(*overlap));
+
+        paginate(matches, offset, length)
+            .into_iter()
+            .map(|(_, user_id, user)| -> app::Result<_> { Ok(UserViewBrief::new(user_id, user)?) })
+            .collect()
+    }
+
+    /// Normalises the stored skills of a page of users and indexes them, for
+    /// contexts that predate the index. Returns how many users were visited.
+    pub fn reindex_skills(&mut self, offset: usize, length: usize) -> app::Result<usize> {
+        self.ensure_admin(&self.current_user())?;
+
+        let users = self
+            .users
+            .entries()
+            .map_err(storage)?
+            .skip(offset)
+            .take(length)
+            .collect::<Vec<_>>();
+
+        let count = users.len();
+
+        for (user_id, mut user) in users {
+            let stored = user.skills.iter().map_err(storage)?.collect::<Vec<_>>();
+
+            user.skills.clear().map_err(storage)?;
+
+            for skill in stored {
+                // legacy skills that no longer pass validation are dropped
+                let Ok(skill) = validate_skill(&self.config, &skill) else {
+                    continue;
+                };
+
+                let _ignored = user.skills.insert(skill.clone()).map_err(storage)?;
+
+                self.index_user_skill(user_id, skill)?;
+            }
+
+            let _ignored = self.users.insert(user_id, user).map_err(storage)?;
+        }
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,701 @@
+//! Export and import of a whole context as JSON.
+//!
+//! [`AppState::export_snapshot`] walks the state section by section and
+//! returns it in chunks, each carrying the cursor of the next one. Feeding
+//! every chunk, in order, to [`AppState::import_snapshot`] on another context
+//! rebuilds the board with the same IDs, timestamps and cross-references.
+//!
+//! Imports never merge into an existing board. The context record, which
+//! opens the first chunk, is only accepted while the target holds no users,
+//! edits, bounties, bids, assignments or messages, and replaces its metadata,
+//! admins and labels. Any other record whose ID is already present is left
+//! as it is, so a chunk that is retried can't change what the first attempt
+//! imported.
+
+use std::collections::BTreeMap;
+
+use calimero_sdk::app;
+use calimero_sdk::serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::alloc::IdStrategy;
+use crate::assignment::{Assignment, AssignmentId, AssignmentStatus};
+use crate::bid::{Bid, BidId, BidStatus, CounterOffer};
+use crate::bounty::{Bounty, BountyId, BountyStatus};
+use crate::config::AppConfig;
+use crate::context::ContextInfo;
+use crate::error::{bail, storage, ErrorCode};
+use crate::label::{Label, LabelId};
+use crate::message::{Message, MessageId, MessageTarget, Reaction, ReactionView};
+use crate::stats::Change;
+use crate::user::{User, UserChange, UserEdit, UserEditId, UserId, UserRemarks};
+use crate::AppState;
+
+/// Version of the snapshot document, bumped whenever a record changes shape.
+///
+/// 2 added bid negotiations, the expiry horizon and negotiation round limit
+/// to the config, and the allocator's counters.
+pub const SNAPSHOT_VERSION: u16 = 2;
+
+/// Oldest version still imported, records it lacks fields for take defaults.
+pub const MIN_SNAPSHOT_VERSION: u16 = 1;
+
+const SNAPSHOT_CHUNK_SIZE: usize = 50;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("unsupported snapshot version {version} (expected {min} to {max})")]
+    UnsupportedSnapshotVersion { version: u16, min: u16, max: u16 },
+    #[error("snapshots can only be imported into an empty context")]
+    ImportTargetNotEmpty,
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::UnsupportedSnapshotVersion { .. } => 11001,
+            Error::ImportTargetNotEmpty => 11002,
+        }
+    }
+}
+
+/// Sections in export order, so records are imported after what they refer to.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum SnapshotSection {
+    Context,
+    Labels,
+    Users,
+    UserEdits,
+    Bounties,
+    Bids,
+    Assignments,
+    Messages,
+}
+
+impl SnapshotSection {
+    fn next(self) -> Option<Self> {
+        let next = match self {
+            Self::Context => Self::Labels,
+            Self::Labels => Self::Users,
+            Self::Users => Self::UserEdits,
+            Self::UserEdits => Self::Bounties,
+            Self::Bounties => Self::Bids,
+            Self::Bids => Self::Assignments,
+            Self::Assignments => Self::Messages,
+            Self::Messages => return None,
+        };
+
+        Some(next)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct SnapshotPage {
+    pub section: SnapshotSection,
+    pub offset: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct SnapshotChunk {
+    pub version: u16,
+    pub records: Vec<SnapshotRecord>,
+    /// Page to export next, `None` once the snapshot is complete.
+    pub next: Option<SnapshotPage>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum SnapshotRecord {
+    Context(ContextRecord),
+    Label(LabelRecord),
+    User(UserRecord),
+    UserEdit(UserEditRecord),
+    Bounty(BountyRecord),
+    Bid(BidRecord),
+    Assignment(AssignmentRecord),
+    Message(MessageRecord),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct ContextRecord {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub created_by: Option<UserId>,
+    pub created_at: Option<u64>,
+    pub admins: Vec<UserId>,
+    pub config: AppConfig,
+    pub id_strategy: IdStrategy,
+    #[serde(default)]
+    pub id_counters: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct LabelRecord {
+    pub id: LabelId,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct UserRecord {
+    pub id: UserId,
+    pub name: Option<String>,
+    pub skills: Vec<String>,
+    pub links: Vec<String>,
+    pub total_reward: u128,
+    pub bids: Vec<BidId>,
+    pub assignments: Vec<AssignmentId>,
+    pub bounties: Vec<BountyId>,
+    pub invitations: Vec<BountyId>,
+    pub messages: Vec<MessageId>,
+    pub remarks: Vec<UserRemarks>,
+    pub edits: Vec<UserEditId>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct UserEditRecord {
+    pub id: UserEditId,
+    pub user: UserId,
+    pub actor: UserId,
+    pub timestamp: u64,
+    pub changes: Vec<UserChange>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BountyRecord {
+    pub id: BountyId,
+    pub title: String,
+    pub author: UserId,
+    pub message: MessageId,
+    pub award: Option<u128>,
+    pub status: BountyStatus,
+    pub is_epic: bool,
+    pub deadline: Option<u64>,
+    pub labels: Vec<LabelId>,
+    pub reviewers: Vec<UserId>,
+    pub bids: Vec<BidId>,
+    pub assignments: Vec<AssignmentId>,
+    pub invitations: Vec<UserId>,
+    pub parent: Option<BountyId>,
+    pub children: Vec<BountyId>,
+    pub triaged_by: Option<UserId>,
+    pub approved_by: Option<UserId>,
+    pub closed_by: Option<UserId>,
+    pub proposed_at: Option<u64>,
+    pub triaged_at: Option<u64>,
+    pub approved_at: Option<u64>,
+    pub closed_at: Option<u64>,
+    pub updated_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BidRecord {
+    pub id: BidId,
+    pub author: UserId,
+    pub bounty: BountyId,
+    pub message: MessageId,
+    pub assignment: Option<AssignmentId>,
+    pub status: BidStatus,
+    pub invited: bool,
+    pub expiry: Option<u64>,
+    pub reward: BTreeMap<String, u128>,
+    pub duration: Option<u64>,
+    pub proposed_at: Option<u64>,
+    pub approved_at: Option<u64>,
+    pub retracted_at: Option<u64>,
+    #[serde(default)]
+    pub negotiation: Vec<CounterOffer>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct AssignmentRecord {
+    pub id: AssignmentId,
+    pub assignee: UserId,
+    pub bounty: BountyId,
+    pub message: MessageId,
+    pub bid: Option<BidId>,
+    pub links: Vec<String>,
+    pub status: AssignmentStatus,
+    pub expiry: Option<u64>,
+    pub reward: BTreeMap<String, u128>,
+    pub duration: Option<u64>,
+    pub assigned_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub completed_at: Option<u64>,
+    pub abandoned_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct MessageRecord {
+    pub id: MessageId,
+    pub author: UserId,
+    pub timestamp: u64,
+    pub target: MessageTarget,
+    pub content: String,
+    pub reactions: Vec<ReactionView>,
+    pub comments: Vec<MessageId>,
+}
+
+impl AppState {
+    fn export_section(
+        &self,
+        section: SnapshotSection,
+        offset: usize,
+        length: usize,
+    ) -> app::Result<Vec<SnapshotRecord>> {
+        macro_rules! export {
+            ($map:expr, $record:ident, $export:path) => {
+                $map.entries()
+                    .map_err(storage)?
+                    .skip(offset)
+                    .take(length)
+                    .map(|(id, entity)| Ok(SnapshotRecord::$record($export(id, entity)?)))
+                    .collect()
+            };
+        }
+
+        match section {
+            SnapshotSection::Context => {
+                if offset > 0 {
+                    return Ok(Vec::new());
+                }
+
+                Ok(vec![SnapshotRecord::Context(ContextRecord {
+                    name: self.context.name.clone(),
+                    description: self.context.description.clone(),
+                    created_by: self.context.created_by,
+                    created_at: self.context.created_at,
+                    admins: self.admins.iter().map_err(storage)?.collect(),
+                    config: self.config.clone(),
+                    id_strategy: self.ids.strategy,
+                    id_counters: self.ids.counters()?,
+                })])
+            }
+            SnapshotSection::Labels => export!(self.labels, Label, export_label),
+            SnapshotSection::Users => export!(self.users, User, export_user),
+            SnapshotSection::UserEdits => export!(self.user_edits, UserEdit, export_user_edit),
+            SnapshotSection::Bounties => export!(self.bounties, Bounty, export_bounty),
+            SnapshotSection::Bids => export!(self.bids, Bid, export_bid),
+            SnapshotSection::Assignments => {
+                export!(self.assignments, Assignment, export_assignment)
+            }
+            SnapshotSection::Messages => export!(self.messages, Message, export_message),
+        }
+    }
+
+    fn has_board(&self) -> app::Result<bool> {
+        Ok(self.users.len().map_err(storage)? > 0
+            || self.user_edits.len().map_err(storage)? > 0
+            || self.bounties.len().map_err(storage)? > 0
+            || self.bids.len().map_err(storage)? > 0
+            || self.assignments.len().map_err(storage)? > 0
+            || self.messages.len().map_err(storage)? > 0)
+    }
+
+    /// Whether the entity a record describes is already present.
+    fn is_imported(&self, record: &SnapshotRecord) -> app::Result<bool> {
+        let imported = match record {
+            SnapshotRecord::Context(_) => false,
+            SnapshotRecord::Label(record) => self.labels.contains(&record.id),
+            SnapshotRecord::User(record) => self.users.contains(&record.id),
+            SnapshotRecord::UserEdit(record) => self.user_edits.contains(&record.id),
+            SnapshotRecord::Bounty(record) => self.bounties.contains(&record.id),
+            SnapshotRecord::Bid(record) => self.bids.contains(&record.id),
+            SnapshotRecord::Assignment(record) => self.assignments.contains(&record.id),
+            SnapshotRecord::Message(record) => self.messages.contains(&record.id),
+        };
+
+        imported.map_err(storage)
+    }
+
+    fn import_record(&mut self, record: SnapshotRecord) -> app::Result<()> {
+        if self.is_imported(&record)? {
+            return Ok(());
+        }
+
+        match record {
+            SnapshotRecord::Context(record) => {
+                if self.has_board()? {
+                    bail!(Error::ImportTargetNotEmpty);
+                }
+
+                record.config.validate()?;
+
+                self.context = ContextInfo {
+                    name: record.name,
+                    description: record.description,
+                    created_by: record.created_by,
+                    created_at: record.created_at,
+                };
+
+                // labels seeded when the context was created are replaced too
+                self.labels.clear().map_err(storage)?;
+                self.admins.clear().map_err(storage)?;
+
+                for admin in record.admins {
+                    let _ignored = self.admins.insert(admin).map_err(storage)?;
+                }
+
+                // whoever runs the import has to stay an admin to finish it
+                let _ignored = self.admins.insert(self.current_user()).map_err(storage)?;
+
+                self.config = record.config;
+                self.ids.strategy = record.id_strategy;
+
+                for (key, value) in record.id_counters {
+                    self.ids.advance_counter(key, value)?;
+                }
+            }
+            SnapshotRecord::Label(record) => {
+                let label = Label { name: record.name };
+
+                let _ignored = self.labels.insert(record.id, label).map_err(storage)?;
+            }
+            SnapshotRecord::User(record) => {
+                for skill in &record.skills {
+                    self.index_user_skill(record.id, skill.clone())?;
+                }
+
+                let user = User {
+                    name: record.name,
+                    skills: record.skills.into_iter().collect(),
+                    links: record.links.into_iter().collect(),
+                    total_reward: record.total_reward,
+                    bids: record.bids.into_iter().collect(),
+                    assignments: record.assignments.into_iter().collect(),
+                    bounties: record.bounties.into_iter().collect(),
+                    invitations: record.invitations.into_iter().collect(),
+                    messages: record.messages.into_iter().collect(),
+                    remarks: record.remarks.into_iter().collect(),
+                    edits: record.edits.into_iter().collect(),
+                };
+
+                let _ignored = self.users.insert(record.id, user).map_err(storage)?;
+            }
+            SnapshotRecord::UserEdit(record) => {
+                let edit = UserEdit {
+                    user: record.user,
+                    actor: record.actor,
+                    timestamp: record.timestamp,
+                    changes: record.changes,
+                };
+
+                let _ignored = self.user_edits.insert(record.id, edit).map_err(storage)?;
+            }
+            SnapshotRecord::Bounty(record) => {
+                let bounty = Bounty {
+                    title: record.title,
+                    author: record.author,
+                    message: record.message,
+                    award: record.award,
+                    status: record.status,
+                    is_epic: record.is_epic,
+                    deadline: record.deadline,
+                    labels: record.labels.into_iter().collect(),
+                    reviewers: record.reviewers.into_iter().collect(),
+                    bids: record.bids.into_iter().collect(),
+                    assignments: record.assignments.into_iter().collect(),
+                    invitations: record.invitations.into_iter().collect(),
+                    parent: record.parent,
+                    children: record.children.into_iter().collect(),
+                    triaged_by: record.triaged_by,
+                    approved_by: record.approved_by,
+                    closed_by: record.closed_by,
+                    proposed_at: record.proposed_at,
+                    triaged_at: record.triaged_at,
+                    approved_at: record.approved_at,
+                    closed_at: record.closed_at,
+                    updated_at: record.updated_at,
+                };
+
+                self.stats.tally_bounty(&bounty, Change::Add)?;
+
+                let _ignored = self.bounties.insert(record.id, bounty).map_err(storage)?;
+
+                self.index_bounty(record.id)?;
+            }
+            SnapshotRecord::Bid(record) => {
+                let bid = Bid {
+                    author: record.author,
+                    bounty: record.bounty,
+                    message: record.message,
+                    assignment: record.assignment,
+                    status: record.status,
+                    invited: record.invited,
+                    expiry: record.expiry,
+                    reward: record.reward.into_iter().collect(),
+                    duration: record.duration,
+                    proposed_at: record.proposed_at,
+                    approved_at: record.approved_at,
+                    retracted_at: record.retracted_at,
+                    negotiation: record.negotiation,
+                };
+
+                self.stats.tally_bid(&bid, Change::Add)?;
+
+                let _ignored = self.bids.insert(record.id, bid).map_err(storage)?;
+            }
+            SnapshotRecord::Assignment(record) => {
+                let assignment = Assignment {
+                    assignee: record.assignee,
+                    bounty: record.bounty,
+                    message: record.message,
+                    bid: record.bid,
+                    links: record.links.into_iter().collect(),
+                    status: record.status,
+                    expiry: record.expiry,
+                    reward: record.reward.into_iter().collect(),
+                    duration: record.duration,
+                    assigned_at: record.assigned_at,
+                    accepted_at: record.accepted_at,
+                    completed_at: record.completed_at,
+                    abandoned_at: record.abandoned_at,
+                };
+
+                self.stats.tally_assignment(&assignment, Change::Add)?;
+
+                let _ignored = self
+                    .assignments
+                    .insert(record.id, assignment)
+                    .map_err(storage)?;
+            }
+            SnapshotRecord::Message(record) => {
+                let reactions = record.reactions.into_iter().map(|reaction| Reaction {
+                    emoji: reaction.emoji,
+                    users: reaction.users.into_iter().collect(),
+                });
+
+                let message = Message {
+                    author: record.author,
+                    timestamp: record.timestamp,
+                    target: record.target,
+                    content: record.content,
+                    reactions: reactions.collect(),
+                    comments: record.comments.into_iter().collect(),
+                };
+
+                // descriptions are indexed along with their bounty
+                let described = match message.target {
+                    MessageTarget::Bounty(bounty_id) => Some(bounty_id),
+                    _ => None,
+                };
+
+                if let Some(bounty_id) = described {
+                    self.unindex_bounty(bounty_id)?;
+                }
+
+                let _ignored = self.messages.insert(record.id, message).map_err(storage)?;
+
+                if let Some(bounty_id) = described {
+                    self.index_bounty(bounty_id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn export_label(id: LabelId, label: Label) -> app::Result<LabelRecord> {
+    Ok(LabelRecord {
+        id,
+        name: label.name,
+    })
+}
+
+fn export_user(id: UserId, user: User) -> app::Result<UserRecord> {
+    Ok(UserRecord {
+        id,
+        name: user.name,
+        skills: user.skills.iter().map_err(storage)?.collect(),
+        links: user.links.iter().map_err(storage)?.collect(),
+        total_reward: user.total_reward,
+        bids: user.bids.iter().map_err(storage)?.collect(),
+        assignments: user.assignments.iter().map_err(storage)?.collect(),
+        bounties: user.bounties.iter().map_err(storage)?.collect(),
+        invitations: user.invitations.iter().map_err(storage)?.collect(),
+        messages: user.messages.iter().map_err(storage)?.collect(),
+        remarks: user.remarks.iter().map_err(storage)?.collect(),
+        edits: user.edits.iter().map_err(storage)?.collect(),
+    })
+}
+
+fn export_user_edit(id: UserEditId, edit: UserEdit) -> app::Result<UserEditRecord> {
+    Ok(UserEditRecord {
+        id,
+        user: edit.user,
+        actor: edit.actor,
+        timestamp: edit.timestamp,
+        changes: edit.changes,
+    })
+}
+
+fn export_bounty(id: BountyId, bounty: Bounty) -> app::Result<BountyRecord> {
+    Ok(BountyRecord {
+        id,
+        title: bounty.title,
+        author: bounty.author,
+        message: bounty.message,
+        award: bounty.award,
+        status: bounty.status,
+        is_epic: bounty.is_epic,
+        deadline: bounty.deadline,
+        labels: bounty.labels.iter().map_err(storage)?.collect(),
+        reviewers: bounty.reviewers.iter().map_err(storage)?.collect(),
+        bids: bounty.bids.iter().map_err(storage)?.collect(),
+        assignments: bounty.assignments.iter().map_err(storage)?.collect(),
+        invitations: bounty.invitations.iter().map_err(storage)?.collect(),
+        parent: bounty.parent,
+        children: bounty.children.iter().map_err(storage)?.collect(),
+        triaged_by: bounty.triaged_by,
+        approved_by: bounty.approved_by,
+        closed_by: bounty.closed_by,
+        proposed_at: bounty.proposed_at,
+        triaged_at: bounty.triaged_at,
+        approved_at: bounty.approved_at,
+        closed_at: bounty.closed_at,
+        updated_at: bounty.updated_at,
+    })
+}
+
+fn export_bid(id: BidId, bid: Bid) -> app::Result<BidRecord> {
+    Ok(BidRecord {
+        id,
+        author: bid.author,
+        bounty: bid.bounty,
+        message: bid.message,
+        assignment: bid.assignment,
+        status: bid.status,
+        invited: bid.invited,
+        expiry: bid.expiry,
+        reward: bid.reward.entries().map_err(storage)?.collect(),
+        duration: bid.duration,
+        proposed_at: bid.proposed_at,
+        approved_at: bid.approved_at,
+        retracted_at: bid.retracted_at,
+        negotiation: bid.negotiation,
+    })
+}
+
+fn export_assignment(id: AssignmentId, assignment: Assignment) -> app::Result<AssignmentRecord> {
+    Ok(AssignmentRecord {
+        id,
+        assignee: assignment.assignee,
+        bounty: assignment.bounty,
+        message: assignment.message,
+        bid: assignment.bid,
+        links: assignment.links.iter().map_err(storage)?.collect(),
+        status: assignment.status,
+        expiry: assignment.expiry,
+        reward: assignment.reward.entries().map_err(storage)?.collect(),
+        duration: assignment.duration,
+        assigned_at: assignment.assigned_at,
+        accepted_at: assignment.accepted_at,
+        completed_at: assignment.completed_at,
+        abandoned_at: assignment.abandoned_at,
+    })
+}
+
+fn export_message(id: MessageId, message: Message) -> app::Result<MessageRecord> {
+    let reactions = message
+        .reactions
+        .iter()
+        .map_err(storage)?
+        .map(|reaction| {
+            Ok(ReactionView {
+                emoji: reaction.emoji,
+                users: reaction.users.entries().map_err(storage)?.collect(),
+            })
+        })
+        .collect::<app::Result<_>>()?;
+
+    Ok(MessageRecord {
+        id,
+        author: message.author,
+        timestamp: message.timestamp,
+        target: message.target,
+        content: message.content,
+        reactions,
+        comments: message.comments.iter().map_err(storage)?.collect(),
+    })
+}
+
+#[app::logic]
+impl AppState {
+    /// Exports one chunk of the context, starting at `page`, or at the
+    /// beginning when it's `None`. Pass each chunk's `next` back in until it
+    /// is `None`.
+    pub fn export_snapshot(&self, page: Option<SnapshotPage>) -> app::Result<SnapshotChunk> {
+        let mut page = page.unwrap_or(SnapshotPage {
+            section: SnapshotSection::Context,
+            offset: 0,
+        });
+
+        let mut records = Vec::new();
+
+        let next = loop {
+            let remaining = SNAPSHOT_CHUNK_SIZE - records.len();
+
+            let section = self.export_section(page.section, page.offset, remaining)?;
+
+            let exported = section.len();
+
+            records.extend(section);
+
+            if exported == remaining {
+                break Some(SnapshotPage {
+                    section: page.section,
+                    offset: page.offset + exported,
+                });
+            }
+
+            let Some(section) = page.section.next() else {
+                break None;
+            };
+
+            page = SnapshotPage { section, offset: 0 };
+        };
+
+        Ok(SnapshotChunk {
+            version: SNAPSHOT_VERSION,
+            records,
+            next,
+        })
+    }
+
+    /// Imports a chunk produced by [`AppState::export_snapshot`], returning
+    /// how many records it held. The first chunk must go to an empty context,
+    /// later ones can be retried, see the [module docs](self).
+    pub fn import_snapshot(&mut self, chunk: SnapshotChunk) -> app::Result<usize> {
+        self.ensure_admin(&self.current_user())?;
+
+        if !(MIN_SNAPSHOT_VERSION..=SNAPSHOT_VERSION).contains(&chunk.version) {
+            bail!(Error::UnsupportedSnapshotVersion {
+                version: chunk.version,
+                min: MIN_SNAPSHOT_VERSION,
+                max: SNAPSHOT_VERSION,
+            });
+        }
+
+        let count = chunk.records.len();
+
+        for record in chunk.records {
+            self.import_record(record)?;
+        }
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,364 @@
+//! Board statistics, kept up to date as entities are written rather than
+//! computed by scanning on every read.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use calimero_storage::collections::UnorderedMap;
+
+use crate::assignment::Assignment;
+use crate::bid::Bid;
+use crate::bounty::{Bounty, BountyStatusLite};
+use crate::env;
+use crate::error::storage;
+use crate::label::LabelId;
+use crate::user::UserId;
+use crate::AppState;
+
+const TOP_LABELS: usize = 10;
+
+#[derive(Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct BountyStatusCounts<T = u64> {
+    pub proposed: T,
+    pub triaged: T,
+    pub approved: T,
+    pub expired: T,
+    pub abandoned: T,
+    pub completed: T,
+}
+
+impl<T> BountyStatusCounts<T> {
+    fn get_mut(&mut self, status: BountyStatusLite) -> &mut T {
+        match status {
+            BountyStatusLite::Proposed => &mut self.proposed,
+            BountyStatusLite::Triaged => &mut self.triaged,
+            BountyStatusLite::Approved => &mut self.approved,
+            BountyStatusLite::Expired => &mut self.expired,
+            BountyStatusLite::Abandoned => &mut self.abandoned,
+            BountyStatusLite::Completed => &mut self.completed,
+        }
+    }
+}
+
+impl BountyStatusCounts<i64> {
+    fn merge(&mut self, other: &Self) {
+        self.proposed = self.proposed.saturating_add(other.proposed);
+        self.triaged = self.triaged.saturating_add(other.triaged);
+        self.approved = self.approved.saturating_add(other.approved);
+        self.expired = self.expired.saturating_add(other.expired);
+        self.abandoned = self.abandoned.saturating_add(other.abandoned);
+        self.completed = self.completed.saturating_add(other.completed);
+    }
+
+    fn counts(&self) -> BountyStatusCounts {
+        BountyStatusCounts {
+            proposed: count(self.proposed),
+            triaged: count(self.triaged),
+            approved: count(self.approved),
+            expired: count(self.expired),
+            abandoned: count(self.abandoned),
+            completed: count(self.completed),
+        }
+    }
+}
+
+impl BountyStatusCounts {
+    fn open(&self) -> u64 {
+        self.proposed
+            .saturating_add(self.triaged)
+            .saturating_add(self.approved)
+    }
+}
+
+/// Clamps a summed tally, which is negative while one executor has synced
+/// counting an entity out but another hasn't yet synced counting it in.
+fn count(tally: i64) -> u64 {
+    u64::try_from(tally).unwrap_or(0)
+}
+
+/// Whether an entity is counted in, or counted back out before it changes.
+#[derive(Clone, Copy, Debug)]
+pub enum Change {
+    Add,
+    Remove,
+}
+
+impl Change {
+    fn sign(self) -> i64 {
+        match self {
+            Change::Add => 1,
+            Change::Remove => -1,
+        }
+    }
+}
+
+/// Sum and count of durations, in nanoseconds like every timestamp.
+#[derive(Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct DurationStat {
+    total: i128,
+    count: i64,
+}
+
+impl DurationStat {
+    fn tally(&mut self, start: Option<u64>, end: Option<u64>, change: Change) {
+        let (Some(start), Some(end)) = (start, end) else {
+            return;
+        };
+
+        let duration = i128::from(end.saturating_sub(start));
+
+        self.total = self
+            .total
+            .saturating_add(duration * i128::from(change.sign()));
+        self.count = self.count.saturating_add(change.sign());
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.total = self.total.saturating_add(other.total);
+        self.count = self.count.saturating_add(other.count);
+    }
+
+    fn average(&self) -> Option<u64> {
+        if self.count <= 0 {
+            return None;
+        }
+
+        let average = self.total.max(0) / i128::from(self.count);
+
+        Some(u64::try_from(average).unwrap_or(u64::MAX))
+    }
+}
+
+/// What one executor has counted, signed since it may count out entities
+/// another executor counted in.
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct Tally {
+    bounties: BountyStatusCounts<i64>,
+    total_award: i128,
+    awarded_bounties: i64,
+    time_to_approval: DurationStat,
+    time_to_completion: DurationStat,
+    bids: i64,
+    labels: BTreeMap<LabelId, i64>,
+}
+
+impl Tally {
+    fn bounty(&mut self, bounty: &Bounty, change: Change) -> app::Result<()> {
+        let status = self
+            .bounties
+            .get_mut(BountyStatusLite::from(&bounty.status));
+
+        *status = status.saturating_add(change.sign());
+
+        if let Some(award) = bounty.award {
+            let award = i128::try_from(award).unwrap_or(i128::MAX);
+
+            self.total_award = match change {
+                Change::Add => self.total_award.saturating_add(award),
+                Change::Remove => self.total_award.saturating_sub(award),
+            };
+            self.awarded_bounties = self.awarded_bounties.saturating_add(change.sign());
+        }
+
+        let (start, end) = (bounty.proposed_at, bounty.approved_at);
+
+        self.time_to_approval.tally(start, end, change);
+
+        for label in bounty.labels.iter().map_err(storage)? {
+            let count = self.labels.entry(label).or_default();
+
+            *count = count.saturating_add(change.sign());
+
+            if *count == 0 {
+                let _ignored = self.labels.remove(&label);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bid(&mut self, change: Change) {
+        self.bids = self.bids.saturating_add(change.sign());
+    }
+
+    fn assignment(&mut self, assignment: &Assignment, change: Change) {
+        let (start, end) = (assignment.assigned_at, assignment.completed_at);
+
+        self.time_to_completion.tally(start, end, change);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.bounties.merge(&other.bounties);
+        self.total_award = self.total_award.saturating_add(other.total_award);
+        self.awarded_bounties = self.awarded_bounties.saturating_add(other.awarded_bounties);
+        self.time_to_approval.merge(&other.time_to_approval);
+        self.time_to_completion.merge(&other.time_to_completion);
+        self.bids = self.bids.saturating_add(other.bids);
+
+        for (label, count) in &other.labels {
+            let total = self.labels.entry(*label).or_default();
+
+            *total = total.saturating_add(*count);
+        }
+    }
+}
+
+/// Tallies are kept per executor, the way the ID allocator keeps its
+/// counters, so nodes counting at the same time never overwrite each other.
+/// Reads add them all up.
+///
+/// Anything counted in has to be counted out, with [`Change::Remove`],
+/// before its status or timestamps change, and back in afterwards.
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+pub struct Stats {
+    tallies: UnorderedMap<UserId, Tally>,
+}
+
+impl Stats {
+    fn update<T>(&mut self, f: impl FnOnce(&mut Tally) -> app::Result<T>) -> app::Result<T> {
+        let executor = UserId::new(env::executor_id());
+
+        let mut tally = self
+            .tallies
+            .get(&executor)
+            .map_err(storage)?
+            .unwrap_or_default();
+
+        let result = f(&mut tally)?;
+
+        let _ignored = self.tallies.insert(executor, tally).map_err(storage)?;
+
+        Ok(result)
+    }
+
+    pub fn tally_bounty(&mut self, bounty: &Bounty, change: Change) -> app::Result<()> {
+        self.update(|tally| tally.bounty(bounty, change))
+    }
+
+    pub fn tally_bid(&mut self, _bid: &Bid, change: Change) -> app::Result<()> {
+        self.update(|tally| {
+            tally.bid(change);
+
+            Ok(())
+        })
+    }
+
+    pub fn tally_assignment(&mut self, assignment: &Assignment, change: Change) -> app::Result<()> {
+        self.update(|tally| {
+            tally.assignment(assignment, change);
+
+            Ok(())
+        })
+    }
+
+    fn total(&self) -> app::Result<Tally> {
+        let mut total = Tally::default();
+
+        for (_, tally) in self.tallies.entries().map_err(storage)? {
+            total.merge(&tally);
+        }
+
+        Ok(total)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct LabelCount {
+    pub label: LabelId,
+    pub name: Option<String>,
+    pub bounties: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct StatsView {
+    pub open_bounties: u64,
+    pub bounties: BountyStatusCounts,
+    pub total_award: u128,
+    pub average_award: Option<u128>,
+    /// Average nanoseconds from proposal to approval.
+    pub average_time_to_approval: Option<u64>,
+    /// Average nanoseconds from assignment to completion.
+    pub average_time_to_completion: Option<u64>,
+    pub bids: u64,
+    pub top_labels: Vec<LabelCount>,
+}
+
+#[app::logic]
+impl AppState {
+    pub fn get_stats(&self) -> app::Result<StatsView> {
+        let stats = self.stats.total()?;
+
+        let mut labels = stats
+            .labels
+            .iter()
+            .map(|(label, tally)| (*label, count(*tally)))
+            .filter(|(_, count)| *count > 0)
+            .collect::<Vec<_>>();
+
+        labels.sort_by_key(|(label, count)| (Reverse(*count), *label));
+
+        let top_labels = labels
+            .into_iter()
+            .take(TOP_LABELS)
+            .map(|(label, count)| {
+                Ok(LabelCount {
+                    label,
+                    name: self
+                        .labels
+                        .get(&label)
+                        .map_err(storage)?
+                        .map(|label| label.name),
+                    bounties: count,
+                })
+            })
+            .collect::<app::Result<_>>()?;
+
+        let bounties = stats.bounties.counts();
+
+        let total_award = u128::try_from(stats.total_award).unwrap_or(0);
+        let awarded_bounties = u128::from(count(stats.awarded_bounties));
+
+        Ok(StatsView {
+            open_bounties: bounties.open(),
+            bounties,
+            total_award,
+            average_award: total_award.checked_div(awarded_bounties),
+            average_time_to_approval: stats.time_to_approval.average(),
+            average_time_to_completion: stats.time_to_completion.average(),
+            bids: count(stats.bids),
+            top_labels,
+        })
+    }
+
+    /// Recounts everything from scratch, into the tally of whoever runs it,
+    /// for contexts that predate the counters or drifted.
+    pub fn recompute_stats(&mut self) -> app::Result<()> {
+        self.ensure_admin(&self.current_user())?;
+
+        self.stats.tallies.clear().map_err(storage)?;
+
+        for (_, bounty) in self.bounties.entries().map_err(storage)? {
+            self.stats.tally_bounty(&bounty, Change::Add)?;
+        }
+
+        for (_, bid) in self.bids.entries().map_err(storage)? {
+            self.stats.tally_bid(&bid, Change::Add)?;
+        }
+
+        for (_, assignment) in self.assignments.entries().map_err(storage)? {
+            self.stats.tally_assignment(&assignment, Change::Add)?;
+        }
+
+        Ok(())
+    }
+}
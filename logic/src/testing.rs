@@ -0,0 +1,58 @@
+//! Native test harness driving the app against [`crate::env::mock`].
+
+use calimero_sdk::app;
+
+use crate::context::Bootstrap;
+use crate::env::mock;
+use crate::user::UserId;
+use crate::AppState;
+
+mod scenarios;
+
+pub fn user(n: u8) -> UserId {
+    UserId::new([n; 32])
+}
+
+pub struct Harness {
+    state: AppState,
+}
+
+impl Harness {
+    /// Boots a fresh context, created by `admin`.
+    pub fn new(admin: UserId) -> Self {
+        Self::with_bootstrap(admin, None)
+    }
+
+    /// Boots a fresh context, created by `admin` with the given `init` payload.
+    pub fn with_bootstrap(admin: UserId, bootstrap: Option<Bootstrap>) -> Self {
+        Self::try_with_bootstrap(admin, bootstrap).expect("init failed")
+    }
+
+    pub fn try_with_bootstrap(admin: UserId, bootstrap: Option<Bootstrap>) -> app::Result<Self> {
+        mock::reset();
+        mock::set_executor_id(**admin);
+
+        Ok(Self {
+            state: AppState::init(bootstrap)?,
+        })
+    }
+
+    /// Switches the executor, every call on the returned state is made as `user`.
+    pub fn as_user(&mut self, user: UserId) -> &mut AppState {
+        mock::set_executor_id(**user);
+
+        &mut self.state
+    }
+
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    pub fn now(&self) -> u64 {
+        mock::time_now()
+    }
+
+    pub fn advance_time(&self, by: u64) {
+        mock::advance_time(by);
+    }
+}
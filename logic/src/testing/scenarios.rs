@@ -0,0 +1,1369 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{user, Harness};
+use crate::activity::ActivityKind;
+use crate::assignment::{
+    AssignmentFilter, AssignmentStatus, AssignmentStatusFilter, DirectAssignmentRequest,
+};
+use crate::bid::{
+    BidSortBy, BidSortField, BidStatusFilter, BidTerms, CreateBidRequest, OfferStatus, Party,
+};
+use crate::bounty::{BountyId, BountyList, CreateBountyRequest, SortOrder};
+use crate::config::AppConfig;
+use crate::context::Bootstrap;
+use crate::message::MessageTarget;
+use crate::preferences::{NotificationKind, NotificationPreferences};
+use crate::snapshot::{SnapshotChunk, SnapshotPage, SnapshotSection, SNAPSHOT_VERSION};
+use crate::user::{DeltaOperation, UserChange, UserDelta, UserId};
+
+fn register(harness: &mut Harness, user_id: UserId, name: &str) {
+    let _user_id = harness
+        .as_user(user_id)
+        .register(Some(name.to_owned()), BTreeSet::new(), BTreeSet::new())
+        .unwrap();
+}
+
+fn create_bounty(harness: &mut Harness, author: UserId, title: &str) -> BountyId {
+    let request = CreateBountyRequest {
+        is_epic: false,
+        title: title.to_owned(),
+        description: format!("{title}, in detail"),
+        reviewers: BTreeSet::new(),
+        labels: BTreeSet::new(),
+        award: Some(100),
+        deadline: None,
+        parent: None,
+    };
+
+    harness.as_user(author).create_bounty(request).unwrap()
+}
+
+fn rename(name: &str) -> UserDelta {
+    UserDelta {
+        name: Some(DeltaOperation::Add(name.to_owned())),
+        skills: Vec::new(),
+        links: Vec::new(),
+    }
+}
+
+#[test]
+fn register_creates_a_profile() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    let user_id = harness
+        .as_user(alice)
+        .register(
+            Some("alice".to_owned()),
+            BTreeSet::from(["Rust ".to_owned()]),
+            BTreeSet::new(),
+        )
+        .unwrap();
+
+    assert_eq!(user_id, alice);
+
+    let view = harness.state().get_user(alice).unwrap().unwrap();
+
+    assert_eq!(view.name.as_deref(), Some("alice"));
+    assert_eq!(view.skills, ["rust"]);
+}
+
+#[test]
+fn register_twice_fails() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let result = harness
+        .as_user(alice)
+        .register(None, BTreeSet::new(), BTreeSet::new());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn create_bounty_requires_registration() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    let request = CreateBountyRequest {
+        is_epic: false,
+        title: "unregistered".to_owned(),
+        description: String::new(),
+        reviewers: BTreeSet::new(),
+        labels: BTreeSet::new(),
+        award: None,
+        deadline: None,
+        parent: None,
+    };
+
+    assert!(harness.as_user(alice).create_bounty(request).is_err());
+}
+
+#[test]
+fn create_bounty_records_author_and_clock() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    harness.advance_time(1_000);
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    let bounty = harness.state().bounties.get(&bounty_id).unwrap().unwrap();
+
+    assert_eq!(bounty.author, alice);
+    assert_eq!(bounty.proposed_at, Some(harness.now()));
+
+    let profile = harness.state().get_user(alice).unwrap().unwrap();
+
+    assert_eq!(profile.bounties, [bounty_id]);
+}
+
+#[test]
+fn create_bid_attaches_to_bounty_and_bidder() {
+    let (alice, bob) = (user(2), user(3));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    let request = CreateBidRequest {
+        brief: "on it".to_owned(),
+        bounty: bounty_id,
+        expiry: None,
+        reward: BTreeMap::from([("near".to_owned(), 100)]),
+        duration: None,
+    };
+
+    let bid_id = harness.as_user(bob).create_bid(request).unwrap();
+
+    let bid = harness.state().bids.get(&bid_id).unwrap().unwrap();
+    let bounty = harness.state().bounties.get(&bounty_id).unwrap().unwrap();
+
+    assert_eq!(bid.author, bob);
+    assert!(bounty.bids.contains(&bid_id).unwrap());
+
+    let profile = harness.state().get_user(bob).unwrap().unwrap();
+
+    assert_eq!(profile.bids, [bid_id]);
+}
+
+#[test]
+fn post_message_threads_under_the_target() {
+    let (alice, bob) = (user(2), user(3));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    let message_id = harness
+        .as_user(bob)
+        .post_message(MessageTarget::Bounty(bounty_id), "which docs?".to_owned())
+        .unwrap();
+
+    let bounty = harness.state().bounties.get(&bounty_id).unwrap().unwrap();
+    let description = harness.state().get_message(bounty.message).unwrap();
+
+    assert_eq!(description.comments, [message_id]);
+
+    let message = harness.state().get_message(message_id).unwrap();
+
+    assert_eq!(message.author, bob);
+    assert_eq!(message.content, "which docs?");
+}
+
+#[test]
+fn list_bounties_pages_through_results() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let created = (0..5)
+        .map(|n| create_bounty(&mut harness, alice, &format!("bounty #{n}")))
+        .collect::<BTreeSet<_>>();
+
+    let (BountyList::Ids(all), _) = harness
+        .state()
+        .list_bounties(None, None, None, None, None, None)
+        .unwrap()
+    else {
+        panic!("expected bare IDs");
+    };
+
+    assert_eq!(all.iter().copied().collect::<BTreeSet<_>>(), created);
+
+    let (BountyList::Ids(page), _) = harness
+        .state()
+        .list_bounties(None, None, None, Some(3), Some(10), None)
+        .unwrap()
+    else {
+        panic!("expected bare IDs");
+    };
+
+    assert_eq!(page, all[3..]);
+
+    let (BountyList::Briefs(briefs), _) = harness
+        .state()
+        .list_bounties(None, None, None, None, Some(2), Some(true))
+        .unwrap()
+    else {
+        panic!("expected briefs");
+    };
+
+    assert_eq!(briefs.len(), 2);
+    assert!(briefs
+        .iter()
+        .all(|entry| entry.as_ref().is_ok_and(|entry| entry.author.is_ok())));
+}
+
+#[test]
+fn batch_views_report_missing_items_individually() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+    let missing = BountyId::new([9; 8]);
+
+    let briefs = harness
+        .state()
+        .get_bounties_brief(vec![bounty_id, missing])
+        .unwrap();
+
+    assert!(briefs[0].is_ok());
+    assert_eq!(briefs[1].as_ref().err().map(|e| e.code), Some(2001));
+
+    let users = harness
+        .state()
+        .get_users_brief(vec![alice, user(7)])
+        .unwrap();
+
+    assert!(users[0].is_ok());
+    assert!(users[1].is_err());
+}
+
+#[test]
+fn batch_views_report_broken_items_individually() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let intact = create_bounty(&mut harness, alice, "fix the docs");
+    let broken = create_bounty(&mut harness, alice, "port the runtime");
+
+    let state = harness.as_user(alice);
+
+    let description = state.bounties.get(&broken).unwrap().unwrap().message;
+
+    let _ignored = state.messages.remove(&description).unwrap();
+
+    let briefs = harness
+        .state()
+        .get_bounties_brief(vec![intact, broken])
+        .unwrap();
+
+    assert!(briefs[0].is_ok());
+    assert_eq!(briefs[1].as_ref().err().map(|e| e.code), Some(9004));
+
+    let (BountyList::Briefs(entries), _) = harness
+        .state()
+        .list_bounties(None, None, None, None, None, Some(true))
+        .unwrap()
+    else {
+        panic!("expected briefs");
+    };
+
+    assert_eq!(entries.iter().filter(|entry| entry.is_ok()).count(), 1);
+    assert_eq!(entries.iter().filter(|entry| entry.is_err()).count(), 1);
+
+    let messages = harness.state().get_messages(vec![description]).unwrap();
+
+    assert_eq!(messages[0].as_ref().err().map(|e| e.code), Some(5001));
+}
+
+#[test]
+fn update_user_is_limited_to_self_and_admins() {
+    let (admin, alice, mallory) = (user(1), user(2), user(3));
+    let mut harness = Harness::new(admin);
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, mallory, "mallory");
+
+    let result = harness.as_user(mallory).update_user(alice, rename("pwned"));
+
+    assert!(result.is_err());
+
+    let profile = harness.state().get_user(alice).unwrap().unwrap();
+
+    assert_eq!(profile.name.as_deref(), Some("alice"));
+
+    harness
+        .as_user(alice)
+        .update_user(alice, rename("alice2"))
+        .unwrap();
+
+    harness
+        .as_user(admin)
+        .update_user(alice, rename("alice3"))
+        .unwrap();
+
+    let profile = harness.state().get_user(alice).unwrap().unwrap();
+
+    assert_eq!(profile.name.as_deref(), Some("alice3"));
+
+    let edits = harness.state().get_user_edits(alice).unwrap();
+    let actors = edits.iter().map(|edit| edit.actor).collect::<Vec<_>>();
+
+    assert_eq!(actors.len(), 2);
+    assert!(actors.contains(&alice) && actors.contains(&admin));
+}
+
+#[test]
+fn user_edits_record_the_stored_values() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let delta = UserDelta {
+        name: Some(DeltaOperation::Add("  alice2 ".to_owned())),
+        skills: vec![DeltaOperation::Add(" Rust ".to_owned())],
+        links: Vec::new(),
+    };
+
+    harness.as_user(alice).update_user(alice, delta).unwrap();
+
+    let edits = harness.state().get_user_edits(alice).unwrap();
+
+    assert!(matches!(
+        edits[0].changes.as_slice(),
+        [
+            UserChange::Name(DeltaOperation::Add(name)),
+            UserChange::Skill(DeltaOperation::Add(skill)),
+        ] if name == "alice2" && skill == "rust"
+    ));
+}
+
+#[test]
+fn update_config_tunes_limits_for_admins_only() {
+    let admin = user(1);
+    let alice = user(2);
+    let mut harness = Harness::new(admin);
+
+    register(&mut harness, alice, "alice");
+
+    let mut config = harness.state().get_config().unwrap();
+    config.max_user_name_length = 3;
+
+    assert!(harness
+        .as_user(alice)
+        .update_config(config.clone())
+        .is_err());
+
+    let unusable = AppConfig {
+        max_bounty_title_length: 0,
+        ..config.clone()
+    };
+
+    assert!(harness.as_user(admin).update_config(unusable).is_err());
+
+    harness.as_user(admin).update_config(config).unwrap();
+
+    let result = harness.as_user(alice).update_user(alice, rename("alicia"));
+
+    assert!(result.is_err());
+
+    harness
+        .as_user(alice)
+        .update_user(alice, rename("ali"))
+        .unwrap();
+}
+
+#[test]
+fn init_applies_the_bootstrap_payload() {
+    let (creator, bob) = (user(1), user(2));
+
+    let bootstrap = Bootstrap {
+        admins: BTreeSet::from([bob]),
+        labels: BTreeSet::from(["bug".to_owned(), " bug ".to_owned(), "docs".to_owned()]),
+        config: Some(AppConfig {
+            max_message_length: 10,
+            ..AppConfig::default()
+        }),
+        name: Some(" Crumb  core ".to_owned()),
+        description: None,
+    };
+
+    let harness = Harness::with_bootstrap(creator, Some(bootstrap));
+
+    let info = harness.state().get_context_info().unwrap();
+
+    assert_eq!(info.name.as_deref(), Some("Crumb core"));
+    assert_eq!(info.created_by, Some(creator));
+    assert_eq!(info.created_at, Some(harness.now()));
+    assert_eq!(info.admins.len(), 2);
+    assert_eq!(
+        info.labels
+            .iter()
+            .map(|l| l.name.as_str())
+            .collect::<Vec<_>>(),
+        ["bug", "docs"]
+    );
+    assert_eq!(info.config.max_message_length, 10);
+}
+
+#[test]
+fn init_rejects_invalid_bootstrap_values() {
+    let creator = user(1);
+
+    let invalid = [
+        Bootstrap {
+            labels: BTreeSet::from(["bug".to_owned(), "b\u{7}ell".to_owned()]),
+            ..Bootstrap::default()
+        },
+        Bootstrap {
+            labels: BTreeSet::from(["   ".to_owned()]),
+            ..Bootstrap::default()
+        },
+        Bootstrap {
+            name: Some("crumb\u{0}".to_owned()),
+            ..Bootstrap::default()
+        },
+        Bootstrap {
+            description: Some("line\u{1b}[31m".to_owned()),
+            ..Bootstrap::default()
+        },
+        Bootstrap {
+            config: Some(AppConfig {
+                max_message_length: 0,
+                ..AppConfig::default()
+            }),
+            ..Bootstrap::default()
+        },
+    ];
+
+    for bootstrap in invalid {
+        assert!(Harness::try_with_bootstrap(creator, Some(bootstrap)).is_err());
+    }
+}
+
+#[test]
+fn snapshots_rebuild_the_board_elsewhere() {
+    let (admin, alice, bob) = (user(1), user(2), user(3));
+    let mut source = Harness::new(admin);
+
+    register(&mut source, alice, "alice");
+    register(&mut source, bob, "bob");
+
+    let bounty_id = create_bounty(&mut source, alice, "fix the docs");
+
+    let message_id = source
+        .as_user(bob)
+        .post_message(MessageTarget::Bounty(bounty_id), "which docs?".to_owned())
+        .unwrap();
+
+    let mut chunks = Vec::new();
+    let mut page = None;
+
+    loop {
+        let chunk = source.state().export_snapshot(page).unwrap();
+
+        page = chunk.next;
+        chunks.push(chunk);
+
+        if page.is_none() {
+            break;
+        }
+    }
+
+    let mut target = Harness::new(admin);
+
+    for chunk in chunks {
+        let _count = target.as_user(admin).import_snapshot(chunk).unwrap();
+    }
+
+    let bounty = target.state().bounties.get(&bounty_id).unwrap().unwrap();
+
+    assert_eq!(bounty.title, "fix the docs");
+
+    let description = target.state().get_message(bounty.message).unwrap();
+
+    assert_eq!(description.comments, [message_id]);
+
+    let profile = target.state().get_user(alice).unwrap().unwrap();
+
+    assert_eq!(profile.bounties, [bounty_id]);
+}
+
+#[test]
+fn snapshots_only_import_into_empty_contexts() {
+    let (admin, alice, bob) = (user(1), user(2), user(3));
+    let mut source = Harness::new(admin);
+
+    register(&mut source, alice, "alice");
+    register(&mut source, bob, "bob");
+
+    let bounty_id = create_bounty(&mut source, alice, "fix the docs");
+
+    let request = CreateBidRequest {
+        brief: "on it".to_owned(),
+        bounty: bounty_id,
+        expiry: None,
+        reward: BTreeMap::from([("near".to_owned(), 100)]),
+        duration: None,
+    };
+
+    let _bid_id = source.as_user(bob).create_bid(request).unwrap();
+
+    let whole = source.state().export_snapshot(None).unwrap();
+    let again = source.state().export_snapshot(None).unwrap();
+
+    assert!(whole.next.is_none());
+
+    let tail = SnapshotPage {
+        section: SnapshotSection::Bounties,
+        offset: 0,
+    };
+
+    let mut retried = source.state().export_snapshot(Some(tail)).unwrap();
+
+    // bids exported before negotiations existed still import
+    retried.version = 1;
+
+    // harnesses share the mocked host, so everything is exported up front
+    let mut target = Harness::new(admin);
+
+    register(&mut target, bob, "bob");
+
+    assert!(target.as_user(admin).import_snapshot(whole).is_err());
+
+    let bootstrap = Bootstrap {
+        admins: BTreeSet::from([user(9)]),
+        labels: BTreeSet::from(["stale".to_owned()]),
+        ..Bootstrap::default()
+    };
+
+    let mut target = Harness::with_bootstrap(admin, Some(bootstrap));
+
+    let _count = target.as_user(admin).import_snapshot(again).unwrap();
+
+    let info = target.state().get_context_info().unwrap();
+
+    assert_eq!(info.admins, [admin]);
+    assert!(info.labels.is_empty());
+
+    let unknown = SnapshotChunk {
+        version: SNAPSHOT_VERSION + 1,
+        records: Vec::new(),
+        next: None,
+    };
+
+    assert!(target.as_user(admin).import_snapshot(unknown).is_err());
+
+    let _count = target.as_user(admin).import_snapshot(retried).unwrap();
+
+    let stats = target.state().get_stats().unwrap();
+
+    assert_eq!(stats.bids, 1);
+    assert_eq!(stats.bounties.proposed, 1);
+}
+
+#[test]
+fn search_bounties_follows_edits() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let docs = create_bounty(&mut harness, alice, "fix the docs");
+    let _other = create_bounty(&mut harness, alice, "port the runtime");
+
+    let hits = harness
+        .state()
+        .search_bounties("Docs".to_owned(), None, None, None)
+        .unwrap();
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].snippets,
+        ["fix the docs", "fix the docs, in detail"]
+    );
+
+    harness
+        .as_user(alice)
+        .edit_bounty(docs, Some("rewrite the guide".to_owned()), None)
+        .unwrap();
+
+    let hits = harness
+        .state()
+        .search_bounties("guide".to_owned(), None, None, None)
+        .unwrap();
+
+    assert_eq!(hits.len(), 1);
+
+    let hits = harness
+        .state()
+        .search_bounties("fix".to_owned(), None, None, None)
+        .unwrap();
+
+    // the description still mentions it
+    assert_eq!(hits[0].snippets, ["fix the docs, in detail"]);
+}
+
+#[test]
+fn stats_track_bounties_and_bids() {
+    let (alice, bob) = (user(2), user(3));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+    let _other = create_bounty(&mut harness, alice, "port the runtime");
+
+    let request = CreateBidRequest {
+        brief: "on it".to_owned(),
+        bounty: bounty_id,
+        expiry: None,
+        reward: BTreeMap::new(),
+        duration: None,
+    };
+
+    let _bid_id = harness.as_user(bob).create_bid(request).unwrap();
+
+    let stats = harness.state().get_stats().unwrap();
+
+    assert_eq!(stats.open_bounties, 2);
+    assert_eq!(stats.bounties.proposed, 2);
+    assert_eq!(stats.total_award, 200);
+    assert_eq!(stats.average_award, Some(100));
+    assert_eq!(stats.average_time_to_approval, None);
+    assert_eq!(stats.bids, 1);
+}
+
+#[test]
+fn stats_saturate_instead_of_overflowing() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let request = |title: &str| CreateBountyRequest {
+        is_epic: false,
+        title: title.to_owned(),
+        description: String::new(),
+        reviewers: BTreeSet::new(),
+        labels: BTreeSet::new(),
+        award: Some(u128::MAX),
+        deadline: None,
+        parent: None,
+    };
+
+    for title in ["fix the docs", "port the runtime"] {
+        let _bounty_id = harness
+            .as_user(alice)
+            .create_bounty(request(title))
+            .unwrap();
+    }
+
+    let stats = harness.state().get_stats().unwrap();
+
+    assert_eq!(stats.bounties.proposed, 2);
+    assert!(stats.total_award > 0);
+}
+
+#[test]
+fn activity_is_filtered_and_paged_newest_first() {
+    let (alice, bob) = (user(2), user(3));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+    let other_id = create_bounty(&mut harness, alice, "port the runtime");
+
+    harness.advance_time(1);
+
+    let question = harness
+        .as_user(bob)
+        .post_message(MessageTarget::Bounty(bounty_id), "which docs?".to_owned())
+        .unwrap();
+
+    harness.advance_time(1);
+
+    let answer = harness
+        .as_user(alice)
+        .post_message(MessageTarget::Message(question), "all of them".to_owned())
+        .unwrap();
+
+    let (bobs, resume) = harness
+        .state()
+        .get_activity(Some(bob), None, None, None)
+        .unwrap();
+
+    assert_eq!(bobs.len(), 1);
+    assert_eq!(bobs[0].bounty, Some(bounty_id));
+    assert!(resume.is_none());
+
+    let (page, resume) = harness
+        .state()
+        .get_activity(None, Some(bounty_id), None, Some(1))
+        .unwrap();
+
+    assert!(matches!(
+        page[0].kind,
+        ActivityKind::Commented { message, .. } if message == answer
+    ));
+
+    let (page, _resume) = harness
+        .state()
+        .get_activity(None, Some(bounty_id), resume, Some(2))
+        .unwrap();
+
+    assert_eq!(page.len(), 2);
+    assert!(matches!(page[1].kind, ActivityKind::BountyCreated));
+    assert!(page
+        .iter()
+        .all(|activity| activity.bounty != Some(other_id)));
+}
+
+#[test]
+fn notifications_reach_those_involved_and_watchers() {
+    let (alice, bob, carol) = (user(2), user(3), user(4));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+    register(&mut harness, carol, "carol");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    harness.as_user(carol).watch_bounty(bounty_id).unwrap();
+
+    let request = CreateBidRequest {
+        brief: "on it".to_owned(),
+        bounty: bounty_id,
+        expiry: None,
+        reward: BTreeMap::new(),
+        duration: None,
+    };
+
+    let _bid_id = harness.as_user(bob).create_bid(request).unwrap();
+
+    harness.advance_time(1);
+
+    let question = harness
+        .as_user(bob)
+        .post_message(MessageTarget::Bounty(bounty_id), "which docs?".to_owned())
+        .unwrap();
+
+    harness.advance_time(1);
+
+    let _answer = harness
+        .as_user(alice)
+        .post_message(MessageTarget::Message(question), "all of them".to_owned())
+        .unwrap();
+
+    let bobs = harness
+        .as_user(bob)
+        .list_notifications(None, None, None)
+        .unwrap();
+
+    assert_eq!(bobs.len(), 1);
+    assert_eq!(bobs[0].kind, NotificationKind::Comment);
+    assert_eq!(bobs[0].actor, alice);
+
+    let carols = harness
+        .as_user(carol)
+        .list_notifications(None, None, None)
+        .unwrap();
+
+    assert_eq!(carols.len(), 3);
+    assert!(carols.iter().all(|n| n.watched && !n.read));
+
+    let carol_state = harness.as_user(carol);
+
+    carol_state.mark_read(vec![carols[0].id]).unwrap();
+
+    let unread = carol_state
+        .list_notifications(Some(true), None, None)
+        .unwrap();
+
+    assert_eq!(unread.len(), 2);
+
+    carol_state
+        .set_notification_preferences(NotificationPreferences {
+            muted_kinds: BTreeSet::from([NotificationKind::Bid]),
+            muted_bounties: BTreeSet::new(),
+        })
+        .unwrap();
+
+    let unread = carol_state
+        .list_notifications(Some(true), None, None)
+        .unwrap();
+
+    assert_eq!(unread.len(), 1);
+
+    carol_state.mark_all_read().unwrap();
+
+    let unread = carol_state
+        .list_notifications(Some(true), None, None)
+        .unwrap();
+
+    assert!(unread.is_empty());
+}
+
+#[test]
+fn notifications_reach_other_bidders_and_the_whole_thread() {
+    let (alice, bob, carol, dave) = (user(2), user(3), user(4), user(5));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+    register(&mut harness, carol, "carol");
+    register(&mut harness, dave, "dave");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    for bidder in [bob, dave] {
+        let request = CreateBidRequest {
+            brief: "on it".to_owned(),
+            bounty: bounty_id,
+            expiry: None,
+            reward: BTreeMap::new(),
+            duration: None,
+        };
+
+        let _bid_id = harness.as_user(bidder).create_bid(request).unwrap();
+
+        harness.advance_time(1);
+    }
+
+    let question = harness
+        .as_user(bob)
+        .post_message(MessageTarget::Bounty(bounty_id), "which docs?".to_owned())
+        .unwrap();
+
+    harness.advance_time(1);
+
+    let answer = harness
+        .as_user(dave)
+        .post_message(MessageTarget::Message(question), "the api ones".to_owned())
+        .unwrap();
+
+    harness.advance_time(1);
+
+    let _aside = harness
+        .as_user(carol)
+        .post_message(MessageTarget::Message(answer), "those are fine".to_owned())
+        .unwrap();
+
+    let actors = |harness: &mut Harness, user_id| {
+        harness
+            .as_user(user_id)
+            .list_notifications(None, None, None)
+            .unwrap()
+            .into_iter()
+            .map(|n| (n.kind, n.actor))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        actors(&mut harness, bob),
+        [
+            (NotificationKind::Comment, carol),
+            (NotificationKind::Comment, dave),
+            (NotificationKind::Bid, dave),
+        ]
+    );
+
+    assert_eq!(
+        actors(&mut harness, dave),
+        [(NotificationKind::Comment, carol)]
+    );
+
+    assert_eq!(actors(&mut harness, alice).len(), 5);
+    assert!(actors(&mut harness, carol).is_empty());
+}
+
+#[test]
+fn counter_offers_renegotiate_bid_terms() {
+    let (alice, bob, carol) = (user(2), user(3), user(4));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+    register(&mut harness, carol, "carol");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    let request = CreateBidRequest {
+        brief: "on it".to_owned(),
+        bounty: bounty_id,
+        expiry: None,
+        reward: BTreeMap::from([("near".to_owned(), 100)]),
+        duration: Some(10),
+    };
+
+    let bid_id = harness.as_user(bob).create_bid(request).unwrap();
+
+    let terms = |amount, duration| BidTerms {
+        reward: BTreeMap::from([("near".to_owned(), amount)]),
+        duration: Some(duration),
+        expiry: None,
+    };
+
+    // only the author can open a negotiation, and only the two sides take part
+    assert!(harness
+        .as_user(bob)
+        .counter_bid(bid_id, terms(90, 10))
+        .is_err());
+    assert!(harness
+        .as_user(carol)
+        .counter_bid(bid_id, terms(90, 10))
+        .is_err());
+
+    harness.advance_time(10);
+
+    let expired = BidTerms {
+        expiry: Some(harness.now() - 1),
+        ..terms(90, 10)
+    };
+
+    assert!(harness.as_user(alice).counter_bid(bid_id, expired).is_err());
+
+    harness
+        .as_user(alice)
+        .counter_bid(bid_id, terms(60, 5))
+        .unwrap();
+
+    assert!(harness
+        .as_user(alice)
+        .counter_bid(bid_id, terms(70, 5))
+        .is_err());
+
+    harness
+        .as_user(bob)
+        .counter_bid(bid_id, terms(80, 8))
+        .unwrap();
+
+    harness.as_user(alice).accept_counter_offer(bid_id).unwrap();
+
+    let bid = harness.state().get_bid(bid_id).unwrap();
+
+    assert_eq!(bid.reward, BTreeMap::from([("near".to_owned(), 80)]));
+    assert_eq!(bid.duration, Some(8));
+    assert_eq!(bid.negotiation.len(), 2);
+    assert!(matches!(bid.negotiation[0].status, OfferStatus::Superseded));
+    assert!(matches!(bid.negotiation[1].status, OfferStatus::Accepted));
+    assert_eq!(bid.negotiation[1].from, Party::Bidder);
+
+    assert!(harness.as_user(bob).accept_counter_offer(bid_id).is_err());
+}
+
+#[test]
+fn negotiations_stop_at_expiry_and_the_round_limit() {
+    let (admin, alice, bob) = (user(1), user(2), user(3));
+    let mut harness = Harness::new(admin);
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    let request = CreateBidRequest {
+        brief: "on it".to_owned(),
+        bounty: bounty_id,
+        expiry: Some(harness.now() + 100),
+        reward: BTreeMap::from([("near".to_owned(), 100)]),
+        duration: Some(10),
+    };
+
+    let bid_id = harness.as_user(bob).create_bid(request).unwrap();
+
+    let terms = |amount, expiry| BidTerms {
+        reward: BTreeMap::from([("near".to_owned(), amount)]),
+        duration: Some(10),
+        expiry,
+    };
+
+    let short_lived = terms(90, Some(harness.now() + 5));
+
+    harness
+        .as_user(alice)
+        .counter_bid(bid_id, short_lived)
+        .unwrap();
+
+    harness.advance_time(10);
+
+    // an offer that lapsed can still be countered, just not accepted
+    assert!(harness.as_user(bob).accept_counter_offer(bid_id).is_err());
+
+    harness
+        .as_user(bob)
+        .counter_bid(bid_id, terms(95, None))
+        .unwrap();
+
+    let mut config = harness.state().get_config().unwrap();
+    config.max_negotiation_rounds = 2;
+
+    harness.as_user(admin).update_config(config).unwrap();
+
+    assert!(harness
+        .as_user(alice)
+        .counter_bid(bid_id, terms(92, None))
+        .is_err());
+
+    harness.advance_time(100);
+
+    assert!(harness.as_user(alice).accept_counter_offer(bid_id).is_err());
+
+    let bid = harness.state().get_bid(bid_id).unwrap();
+
+    assert_eq!(bid.reward, BTreeMap::from([("near".to_owned(), 100)]));
+    assert!(matches!(bid.negotiation[1].status, OfferStatus::Open));
+}
+
+#[test]
+fn list_bids_sorts_and_filters_for_comparison() {
+    let (alice, bob, carol) = (user(2), user(3), user(4));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+    register(&mut harness, carol, "carol");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    let bid = |amount, duration| CreateBidRequest {
+        brief: format!("{amount} in {duration} days"),
+        bounty: bounty_id,
+        expiry: None,
+        reward: BTreeMap::from([("near".to_owned(), amount)]),
+        duration: Some(duration),
+    };
+
+    let cheap = harness.as_user(bob).create_bid(bid(50, 9)).unwrap();
+
+    harness.advance_time(1);
+
+    let quick = harness.as_user(carol).create_bid(bid(120, 2)).unwrap();
+
+    let newest_first = harness
+        .state()
+        .list_bids(bounty_id, None, None, None, None)
+        .unwrap();
+
+    let ids = newest_first.iter().map(|bid| bid.id).collect::<Vec<_>>();
+
+    assert_eq!(ids, [quick, cheap]);
+    assert_eq!(newest_first[1].brief, "50 in 9 days");
+    assert!(newest_first[1].proposed_at.is_some());
+
+    let sortby = BidSortBy {
+        field: BidSortField::Reward,
+        order: SortOrder::Ascending,
+    };
+
+    let by_reward = harness
+        .state()
+        .list_bids(
+            bounty_id,
+            Some(BidStatusFilter::Proposed),
+            Some(sortby),
+            None,
+            Some(1),
+        )
+        .unwrap();
+
+    assert_eq!(by_reward.len(), 1);
+    assert_eq!(by_reward[0].id, cheap);
+}
+
+#[test]
+fn direct_assignments_and_invitations_are_checked() {
+    let (alice, bob) = (user(2), user(3));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    harness.advance_time(10);
+
+    let horizon = harness.state().get_config().unwrap().max_expiry_horizon;
+
+    let request = |expiry| DirectAssignmentRequest {
+        bounty: bounty_id,
+        assignee: bob,
+        brief: "all yours".to_owned(),
+        expiry: Some(expiry),
+        reward: BTreeMap::new(),
+        duration: None,
+    };
+
+    let (past, far) = (harness.now() - 1, harness.now() + horizon + 1);
+
+    assert!(harness
+        .as_user(alice)
+        .assign_directly(request(past))
+        .is_err());
+    assert!(harness
+        .as_user(alice)
+        .assign_directly(request(far))
+        .is_err());
+
+    assert!(harness
+        .as_user(alice)
+        .invite_user(bounty_id, alice)
+        .is_err());
+
+    harness.as_user(alice).invite_user(bounty_id, bob).unwrap();
+}
+
+#[test]
+fn assignment_views_count_down_to_expiry() {
+    let (alice, bob) = (user(2), user(3));
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+    let _other = create_bounty(&mut harness, alice, "port the runtime");
+
+    let request = DirectAssignmentRequest {
+        bounty: bounty_id,
+        assignee: bob,
+        brief: "all yours".to_owned(),
+        expiry: Some(harness.now() + 100),
+        reward: BTreeMap::from([("near".to_owned(), 100)]),
+        duration: Some(50),
+    };
+
+    let assignment_id = harness.as_user(alice).assign_directly(request).unwrap();
+
+    harness
+        .as_user(bob)
+        .accept_assignment(assignment_id)
+        .unwrap();
+
+    let accepted_at = harness.now();
+
+    harness.advance_time(30);
+
+    let view = harness.state().get_assignment(assignment_id).unwrap();
+
+    assert_eq!(view.assignee, bob);
+    assert_eq!(view.time_remaining, Some(70));
+    assert_eq!(view.due_at, Some(accepted_at + 50));
+    assert!(matches!(view.status, AssignmentStatus::InProgress));
+
+    let filter = AssignmentFilter {
+        assignee: Some(bob),
+        bounty: None,
+        status: Some(AssignmentStatusFilter::InProgress),
+    };
+
+    let listed = harness
+        .state()
+        .list_assignments(Some(filter), None, None)
+        .unwrap();
+
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, assignment_id);
+
+    let filter = AssignmentFilter {
+        assignee: None,
+        bounty: Some(bounty_id),
+        status: Some(AssignmentStatusFilter::Completed),
+    };
+
+    let listed = harness
+        .state()
+        .list_assignments(Some(filter), None, None)
+        .unwrap();
+
+    assert!(listed.is_empty());
+}
+
+#[test]
+fn deliverable_links_over_the_limit_are_not_stored() {
+    let (admin, alice, bob) = (user(1), user(2), user(3));
+    let mut harness = Harness::new(admin);
+
+    register(&mut harness, alice, "alice");
+    register(&mut harness, bob, "bob");
+
+    let mut config = harness.state().get_config().unwrap();
+    config.max_deliverable_links = 2;
+
+    harness.as_user(admin).update_config(config).unwrap();
+
+    let bounty_id = create_bounty(&mut harness, alice, "fix the docs");
+
+    let request = DirectAssignmentRequest {
+        bounty: bounty_id,
+        assignee: bob,
+        brief: "all yours".to_owned(),
+        expiry: None,
+        reward: BTreeMap::new(),
+        duration: None,
+    };
+
+    let assignment_id = harness.as_user(alice).assign_directly(request).unwrap();
+
+    harness
+        .as_user(bob)
+        .accept_assignment(assignment_id)
+        .unwrap();
+
+    let links = |links: &[&str]| links.iter().map(|&link| link.to_owned()).collect();
+
+    harness
+        .as_user(bob)
+        .add_deliverable_links(
+            assignment_id,
+            links(&["https://a.example", "https://b.example"]),
+        )
+        .unwrap();
+
+    let result = harness
+        .as_user(bob)
+        .add_deliverable_links(assignment_id, links(&["https://c.example"]));
+
+    assert!(result.is_err());
+
+    // links that are already there don't count against the limit
+    harness
+        .as_user(bob)
+        .add_deliverable_links(assignment_id, links(&["https://A.example/"]))
+        .unwrap();
+
+    let view = harness.state().get_assignment(assignment_id).unwrap();
+
+    let urls = view
+        .links
+        .iter()
+        .map(|link| link.url.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(urls.len(), 2);
+    assert!(!urls.contains(&"https://c.example"));
+}
+
+#[test]
+fn links_are_removed_the_way_they_were_added() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let link = |op: DeltaOperation<String>| UserDelta {
+        name: None,
+        skills: Vec::new(),
+        links: vec![op],
+    };
+
+    let state = harness.as_user(alice);
+
+    state
+        .update_user(
+            alice,
+            link(DeltaOperation::Add("  https://Example.com/me/ ".to_owned())),
+        )
+        .unwrap();
+
+    let remove = || {
+        link(DeltaOperation::Remove(Some(
+            " HTTPS://example.com/me".to_owned(),
+        )))
+    };
+
+    state.update_user(alice, remove()).unwrap();
+
+    let profile = state.get_user(alice).unwrap().unwrap();
+
+    assert!(profile.links.is_empty());
+
+    assert!(state.update_user(alice, remove()).is_err());
+}
+
+#[test]
+fn reindexing_drops_legacy_skills_over_the_limit() {
+    let (admin, alice) = (user(1), user(2));
+    let mut harness = Harness::new(admin);
+
+    register(&mut harness, alice, "alice");
+
+    let state = harness.as_user(admin);
+
+    let mut profile = state.users.get(&alice).unwrap().unwrap();
+
+    let _ignored = profile.skills.insert("Rust".to_owned()).unwrap();
+    let _ignored = profile.skills.insert("x".repeat(50)).unwrap();
+
+    let _ignored = state.users.insert(alice, profile).unwrap();
+
+    let _visited = state.reindex_skills(0, 10).unwrap();
+
+    let profile = state.get_user(alice).unwrap().unwrap();
+
+    assert_eq!(profile.skills, ["rust"]);
+}
+
+#[test]
+fn skills_are_removed_the_way_they_were_added() {
+    let alice = user(2);
+    let mut harness = Harness::new(user(1));
+
+    register(&mut harness, alice, "alice");
+
+    let skill = |op: DeltaOperation<String>| UserDelta {
+        name: None,
+        skills: vec![op],
+        links: Vec::new(),
+    };
+
+    let state = harness.as_user(alice);
+
+    assert!(state
+        .update_user(alice, skill(DeltaOperation::Add("   ".to_owned())))
+        .is_err());
+
+    state
+        .update_user(
+            alice,
+            skill(DeltaOperation::Add(" Smart   Contracts ".to_owned())),
+        )
+        .unwrap();
+
+    let found = state
+        .search_users(None, vec!["smart contracts".to_owned()], None, None)
+        .unwrap();
+
+    assert_eq!(found.len(), 1);
+
+    state
+        .update_user(
+            alice,
+            skill(DeltaOperation::Remove(Some("SMART contracts".to_owned()))),
+        )
+        .unwrap();
+
+    let profile = state.get_user(alice).unwrap().unwrap();
+
+    assert!(profile.skills.is_empty());
+}
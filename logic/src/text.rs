@@ -0,0 +1,112 @@
+//! Normalisation and measurement of user-provided text.
+//!
+//! Every free-form string goes through [`normalize`] before it is validated
+//! or stored, and lengths are measured with [`measure`], so limits mean the
+//! same thing across modules regardless of script.
+
+use calimero_sdk::app;
+use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use calimero_sdk::serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::error::{bail, ErrorCode};
+
+#[derive(Default, Clone, Copy, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum LengthPolicy {
+    /// UTF-8 encoded length.
+    Bytes,
+    /// Unicode scalar values.
+    #[default]
+    Chars,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TextKind {
+    /// Names, titles, skills and links, all whitespace collapses to a single space.
+    Line,
+    /// Descriptions and messages, line breaks and tabs are preserved.
+    Multiline,
+}
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("{field} contains a control character (U+{code:04X})")]
+    ControlCharacter { field: &'static str, code: u32 },
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::ControlCharacter { .. } => 8001,
+        }
+    }
+}
+
+pub fn measure(s: &str, policy: LengthPolicy) -> usize {
+    match policy {
+        LengthPolicy::Bytes => s.len(),
+        LengthPolicy::Chars => s.chars().count(),
+    }
+}
+
+/// Trims and normalises whitespace, rejecting control characters.
+pub fn normalize(field: &'static str, s: &str, kind: TextKind) -> app::Result<String> {
+    let s = s.trim();
+
+    let is_whitespace = |c: &char| matches!(c, '\n' | '\r' | '\t');
+
+    if let Some(c) = s.chars().find(|c| c.is_control() && !is_whitespace(c)) {
+        bail!(Error::ControlCharacter {
+            field,
+            code: c as u32,
+        });
+    }
+
+    let normalized = match kind {
+        TextKind::Line => s.split_whitespace().collect::<Vec<_>>().join(" "),
+        TextKind::Multiline => s
+            .replace("\r\n", "\n")
+            .replace('\r', "\n")
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_collapse_whitespace() {
+        let s = normalize("title", "  fix \t the\u{3000}docs ", TextKind::Line).unwrap();
+
+        assert_eq!(s, "fix the docs");
+    }
+
+    #[test]
+    fn multiline_keeps_line_breaks() {
+        let s = normalize("description", " a  \r\nb\t\n", TextKind::Multiline).unwrap();
+
+        assert_eq!(s, "a\nb");
+    }
+
+    #[test]
+    fn control_characters_are_rejected() {
+        assert!(normalize("title", "a\u{7}b", TextKind::Line).is_err());
+        assert!(normalize("description", "a\u{0}b", TextKind::Multiline).is_err());
+    }
+
+    #[test]
+    fn chars_are_counted_not_bytes() {
+        assert_eq!(measure("日本語", LengthPolicy::Chars), 3);
+        assert_eq!(measure("日本語", LengthPolicy::Bytes), 9);
+    }
+}
@@ -6,8 +6,10 @@ use std::borrow::Cow;
 use std::mem;
 
 use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use calimero_sdk::env;
 use calimero_sdk::serde::{self, de, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::env;
 
 enum Dud<const N: usize> {}
 
@@ -43,15 +45,27 @@ impl<const N: usize, const S: usize> Id<N, S> {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum IdParseError {
+    #[error("invalid base58 encoding: {0}")]
+    InvalidEncoding(#[from] bs58::decode::Error),
+    #[error("invalid id length (expected {expected} bytes, got {actual})")]
+    InvalidLength { expected: usize, actual: usize },
+}
+
 impl<const N: usize, const S: usize> FromStr for Id<N, S> {
-    type Err = bs58::decode::Error;
+    type Err = IdParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut buf = [0; N];
+        let decoded = bs58::decode(s).into_vec()?;
 
-        let _len = bs58::decode(s).onto(&mut buf[..])?;
+        let bytes =
+            <[u8; N]>::try_from(decoded).map_err(|decoded| IdParseError::InvalidLength {
+                expected: N,
+                actual: decoded.len(),
+            })?;
 
-        Ok(Self::new(buf))
+        Ok(Self::new(bytes))
     }
 }
 
@@ -103,16 +117,15 @@ impl<'de, const N: usize, const S: usize> Deserialize<'de> for Id<N, S> {
     where
         D: serde::Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(crate = "calimero_sdk::serde")]
-        struct Container<'a>(#[serde(borrow)] Cow<'a, str>);
-
-        let encoded = Container::deserialize(deserializer)?;
-
-        Self::from_str(&*encoded.0).map_err(de::Error::custom)
+        __private::deserialize_str(deserializer)
     }
 }
 
+/// Exposes a defined ID's prefix to generic code.
+pub trait Prefixed {
+    const PREFIX: Option<&'static str>;
+}
+
 pub trait IdExt<const N: usize, const M: usize>: Sized {
     fn random() -> Self
     where
@@ -175,17 +188,49 @@ pub mod __private {
 
     pub use bs58;
     pub use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
-    pub use calimero_sdk::serde::{Deserialize, Serialize};
+    pub use calimero_sdk::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::*;
+
+    pub fn deserialize_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        #[derive(Deserialize)]
+        #[serde(crate = "calimero_sdk::serde")]
+        struct Container<'a>(#[serde(borrow)] Cow<'a, str>);
+
+        let encoded = Container::deserialize(deserializer)?;
+
+        T::from_str(&encoded.0).map_err(de::Error::custom)
+    }
+
+    /// Strips `{prefix}_` from `s`, if present.
+    pub fn strip_prefix<'a>(s: &'a str, prefix: Option<&str>) -> &'a str {
+        let stripped = prefix
+            .and_then(|prefix| s.strip_prefix(prefix))
+            .and_then(|s| s.strip_prefix('_'));
+
+        stripped.unwrap_or(s)
+    }
 }
 
 macro_rules! define {
-    ($name:ident < $len:literal $(, $str:literal )? >) => {
-        $crate::types::id::define!(@ () $name < $len $(, $str )? >);
+    ($name:ident < $len:literal $(, $str:literal )? > $( as $prefix:literal )?) => {
+        $crate::types::id::define!(@ () $name < $len $(, $str )? > [ $( $prefix )? ]);
+    };
+    (pub $name:ident < $len:literal $(, $str:literal )?> $( as $prefix:literal )?) => {
+        $crate::types::id::define!(@ (pub) $name < $len $(, $str )? > [ $( $prefix )? ]);
+    };
+    (@prefix) => {
+        None
     };
-    (pub $name:ident < $len:literal $(, $str:literal )?>) => {
-        $crate::types::id::define!(@ (pub) $name < $len $(, $str )? >);
+    (@prefix $prefix:literal) => {
+        Some($prefix)
     };
-    (@ ( $($vis:tt)* ) $name:ident < $len:literal $(, $str:literal )? >) => {
+    (@ ( $($vis:tt)* ) $name:ident < $len:literal $(, $str:literal )? > [ $( $prefix:literal )? ]) => {
         #[derive(
             $crate::types::id::__private::Eq,
             $crate::types::id::__private::Ord,
@@ -194,17 +239,17 @@ macro_rules! define {
             $crate::types::id::__private::Debug,
             $crate::types::id::__private::PartialEq,
             $crate::types::id::__private::PartialOrd,
-            $crate::types::id::__private::Serialize,
-            $crate::types::id::__private::Deserialize,
             $crate::types::id::__private::BorshSerialize,
             $crate::types::id::__private::BorshDeserialize,
         )]
         #[borsh(crate = "::calimero_sdk::borsh")]
-        #[serde(crate = "::calimero_sdk::serde")]
         #[repr(transparent)]
         $($vis)* struct $name($crate::types::id::Id< $len $(, $str)? >);
 
         impl $name {
+            /// Printed as `{PREFIX}_` before the encoded bytes, optional when parsing.
+            pub const PREFIX: Option<&'static str> = $crate::types::id::define!(@prefix $( $prefix )?);
+
             pub const fn new(id: [u8; $len]) -> Self {
                 Self::from_id($crate::types::id::Id::new(id))
             }
@@ -218,6 +263,10 @@ macro_rules! define {
             }
         }
 
+        impl $crate::types::id::Prefixed for $name {
+            const PREFIX: Option<&'static str> = $name::PREFIX;
+        }
+
         impl $crate::types::id::__private::Deref for $name {
             type Target = $crate::types::id::Id< $len $(, $str)? >;
 
@@ -243,14 +292,19 @@ macro_rules! define {
                 &self,
                 f: &mut $crate::types::id::__private::fmt::Formatter<'_>
             ) -> $crate::types::id::__private::fmt::Result {
-                $crate::types::id::__private::fmt::Display::fmt(&self.0, f)
+                match Self::PREFIX {
+                    Some(prefix) => f.pad(&format!("{}_{}", prefix, self.0)),
+                    None => $crate::types::id::__private::fmt::Display::fmt(&self.0, f),
+                }
             }
         }
 
         impl $crate::types::id::__private::FromStr for $name {
-            type Err = $crate::types::id::__private::bs58::decode::Error;
+            type Err = $crate::types::id::IdParseError;
 
             fn from_str(s: &str) -> $crate::types::id::__private::Result<Self, Self::Err> {
+                let s = $crate::types::id::__private::strip_prefix(s, Self::PREFIX);
+
                 $crate::types::id::__private::Result::map(
                     $crate::types::id::__private::FromStr::from_str(s),
                     Self::from_id
@@ -258,6 +312,24 @@ macro_rules! define {
             }
         }
 
+        impl $crate::types::id::__private::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> $crate::types::id::__private::Result<S::Ok, S::Error>
+            where
+                S: $crate::types::id::__private::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> $crate::types::id::__private::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> $crate::types::id::__private::Result<Self, D::Error>
+            where
+                D: $crate::types::id::__private::Deserializer<'de>,
+            {
+                $crate::types::id::__private::deserialize_str(deserializer)
+            }
+        }
+
         // SAFETY: the macro guarantees the newtype is the same
         //         size as Id<N, _>
         unsafe impl $crate::types::id::IdTransmute<$len> for $name {}
@@ -265,3 +337,100 @@ macro_rules! define {
 }
 
 pub(crate) use define;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    define!(Prefixed<8, 12> as "pfx");
+    define!(Bare<8, 12>);
+
+    const BYTES: [u8; 8] = *b"crumbid!";
+
+    #[test]
+    fn display_includes_the_prefix() {
+        let encoded = bs58::encode(BYTES).into_string();
+
+        assert_eq!(Prefixed::new(BYTES).to_string(), format!("pfx_{encoded}"));
+        assert_eq!(Bare::new(BYTES).to_string(), encoded);
+    }
+
+    #[test]
+    fn parsing_accepts_prefixed_and_bare_forms() {
+        let encoded = bs58::encode(BYTES).into_string();
+
+        let prefixed = format!("pfx_{encoded}").parse::<Prefixed>().unwrap();
+        let bare = encoded.parse::<Prefixed>().unwrap();
+
+        assert_eq!(prefixed, Prefixed::new(BYTES));
+        assert_eq!(bare, Prefixed::new(BYTES));
+    }
+
+    #[test]
+    fn parsing_rejects_a_foreign_prefix() {
+        let encoded = bs58::encode(BYTES).into_string();
+
+        assert!(format!("msg_{encoded}").parse::<Prefixed>().is_err());
+    }
+
+    #[test]
+    fn parsing_rejects_short_input() {
+        let encoded = bs58::encode(&BYTES[..7]).into_string();
+
+        let error = encoded.parse::<Bare>().unwrap_err();
+
+        assert!(matches!(
+            error,
+            IdParseError::InvalidLength {
+                expected: 8,
+                actual: 7
+            }
+        ));
+    }
+
+    #[test]
+    fn parsing_rejects_long_input() {
+        let encoded = bs58::encode(b"crumbid!!").into_string();
+
+        let error = encoded.parse::<Bare>().unwrap_err();
+
+        assert!(matches!(
+            error,
+            IdParseError::InvalidLength {
+                expected: 8,
+                actual: 9
+            }
+        ));
+    }
+
+    macro_rules! roundtrip {
+        ($($test:ident: $ty:ty),* $(,)?) => {$(
+            #[test]
+            fn $test() {
+                for _ in 0..256 {
+                    let id = <$ty>::random();
+
+                    assert_eq!(id.to_string().parse::<$ty>().unwrap(), id);
+
+                    let bare = bs58::encode(id).into_string();
+
+                    assert_eq!(bare.parse::<$ty>().unwrap(), id);
+                }
+            }
+        )*};
+    }
+
+    roundtrip! {
+        roundtrip_activity_id: crate::activity::ActivityId,
+        roundtrip_assignment_id: crate::assignment::AssignmentId,
+        roundtrip_bid_id: crate::bid::BidId,
+        roundtrip_bounty_id: crate::bounty::BountyId,
+        roundtrip_draft_id: crate::draft::DraftId,
+        roundtrip_label_id: crate::label::LabelId,
+        roundtrip_message_id: crate::message::MessageId,
+        roundtrip_resumption_token: crate::paging::ResumptionToken,
+        roundtrip_state_key: crate::private::StateKey,
+        roundtrip_user_edit_id: crate::user::UserEditId,
+        roundtrip_user_id: crate::user::UserId,
+    }
+}
@@ -1,136 +1,220 @@
+use std::cmp::Reverse;
 use std::collections::BTreeSet;
-use std::sync::LazyLock;
 
+use calimero_sdk::app;
 use calimero_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use calimero_sdk::serde::{Deserialize, Serialize};
-use calimero_sdk::{app, env};
 use calimero_storage::collections::UnorderedSet;
 use thiserror::Error;
 
-use crate::bid::BidId;
-use crate::bounty::BountyId;
+use crate::assignment::{AssignmentId, AssignmentStatusFilter};
+use crate::bid::{BidId, BidStatusFilter};
+use crate::bounty::{BountyId, BountyStatusFilter};
+use crate::config::AppConfig;
+use crate::env;
+use crate::error::{bail, storage, ErrorCode, InternalError, ItemResult};
+use crate::link::{canonical_link, LinkView};
 use crate::message::MessageId;
+use crate::paging::paginate;
+use crate::schema;
+use crate::skill::normalize_skill;
+use crate::text::{self, TextKind};
 use crate::types::id;
 use crate::utils::truncate_string;
 use crate::AppState;
 
-id::define!(pub UserId<32, 44>);
+id::define!(pub UserId<32, 44> as "usr");
+id::define!(pub UserEditId<8, 12> as "edt");
 
-const MAX_USER_NAME_LENGTH: usize = 100;
-const MAX_USER_SKILL_LENGTH: usize = 20;
-const MAX_USER_LINK_LENGTH: usize = 300;
+/// How many of each related entity [`UserView`] carries inline.
+pub const PROFILE_PREVIEW_LENGTH: usize = 3;
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "calimero_sdk::borsh")]
-pub struct User {
-    pub name: Option<String>,
-    pub skills: UnorderedSet<String>,
-    pub links: UnorderedSet<String>,
+schema::versioned! {
+    #[derive(Debug)]
+    @version(2)
+    pub struct User {
+        pub name: Option<String>,
+        pub skills: UnorderedSet<String>,
+        pub links: UnorderedSet<String>,
 
-    pub total_reward: u128,
+        pub total_reward: u128,
 
-    pub bids: UnorderedSet<BidId>,
-    pub assignments: UnorderedSet<BidId>,
-    pub bounties: UnorderedSet<BountyId>,
+        pub bids: UnorderedSet<BidId>,
+        // stored as `UnorderedSet<BidId>` before versioning, which encodes
+        // the same, and was never written to
+        pub assignments: UnorderedSet<AssignmentId>,
+        pub bounties: UnorderedSet<BountyId>,
+        @since(2)
+        pub invitations: UnorderedSet<BountyId>,
 
-    pub messages: UnorderedSet<MessageId>,
-    pub remarks: UnorderedSet<UserRemarks>,
+        pub messages: UnorderedSet<MessageId>,
+        pub remarks: UnorderedSet<UserRemarks>,
+
+        @since(2)
+        pub edits: UnorderedSet<UserEditId>,
+    }
 }
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
+#[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
 pub struct UserRemarks {
     pub review: f32, // 0.0 - 5.0
     pub message: MessageId,
 }
 
+schema::versioned! {
+    #[derive(Debug)]
+    @version(1)
+    pub struct UserEdit {
+        pub user: UserId,
+        pub actor: UserId,
+        pub timestamp: u64,
+        pub changes: Vec<UserChange>,
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
+#[serde(crate = "calimero_sdk::serde")]
+pub enum UserChange {
+    Name(DeltaOperation<String>),
+    Skill(DeltaOperation<String>),
+    Link(DeltaOperation<String>),
+}
+
 #[derive(Debug, Error, Serialize)]
 #[serde(crate = "calimero_sdk::serde")]
 #[serde(tag = "kind", content = "data")]
-pub enum Error<'a> {
-    #[error("user is not registered")]
-    UserNotRegistered,
-    #[error("user is already registered")]
-    UserAlreadyRegistered,
+pub enum Error {
+    #[error("user is not registered: {0}")]
+    UserNotRegistered(UserId),
+    #[error("user {actor} is not allowed to edit {user}")]
+    UserEditForbidden { actor: UserId, user: UserId },
+    #[error("user is already registered: {0}")]
+    UserAlreadyRegistered(UserId),
     #[error("username cannot be empty")]
     UserNameCannotBeEmpty,
-    #[error("username is too long ({max} > {got})")]
+    #[error("username is too long ({got} > {max})")]
     UserNameTooLong { max: usize, got: usize },
-    #[error("user skill is too long ({max} > {got}): {skill}")]
+    #[error("user skill cannot be empty")]
+    UserSkillCannotBeEmpty,
+    #[error("user skill is too long ({got} > {max}): {skill}")]
     UserSkillTooLong {
-        skill: &'a str,
+        skill: String,
         max: usize,
         got: usize,
     },
-    #[error("user link is too long ({max} > {got}): {link}")]
+    #[error("user link is too long ({got} > {max}): {link}")]
     UserLinkTooLong {
-        link: &'a str,
+        link: String,
         max: usize,
         got: usize,
     },
+    #[error("user has no such link: {0}")]
+    UserLinkNotFound(String),
 }
 
-static EXECUTOR_ID: LazyLock<UserId> = std::sync::LazyLock::new(|| UserId::new(env::executor_id()));
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::UserNotRegistered(_) => 1001,
+            Error::UserEditForbidden { .. } => 1002,
+            Error::UserAlreadyRegistered(_) => 1003,
+            Error::UserNameCannotBeEmpty => 1004,
+            Error::UserNameTooLong { .. } => 1005,
+            Error::UserSkillTooLong { .. } => 1006,
+            Error::UserLinkTooLong { .. } => 1007,
+            Error::UserSkillCannotBeEmpty => 1008,
+            Error::UserLinkNotFound(_) => 1009,
+        }
+    }
+
+    fn entity(&self) -> Option<String> {
+        match self {
+            Error::UserNotRegistered(id)
+            | Error::UserAlreadyRegistered(id)
+            | Error::UserEditForbidden { user: id, .. } => Some(id.to_string()),
+            _ => None,
+        }
+    }
+}
 
 impl AppState {
     pub fn current_user(&self) -> UserId {
-        *EXECUTOR_ID
+        UserId::new(env::executor_id())
     }
 
     pub fn ensure_registered_user(&self, user_id: &UserId) -> app::Result<()> {
-        if !self.users.contains(user_id)? {
-            app::bail!(Error::UserNotRegistered);
+        if !self.users.contains(user_id).map_err(storage)? {
+            bail!(Error::UserNotRegistered(*user_id));
         }
 
         Ok(())
     }
 
     pub fn get_registered_user(&self, user_id: &UserId) -> app::Result<User> {
-        let Some(user) = self.users.get(user_id)? else {
-            app::bail!(Error::UserNotRegistered);
+        let Some(user) = self.users.get(user_id).map_err(storage)? else {
+            bail!(Error::UserNotRegistered(*user_id));
         };
 
         Ok(user)
     }
 }
 
-fn validate_user_name(name: &str) -> app::Result<()> {
+fn validate_user_name(config: &AppConfig, name: &str) -> app::Result<String> {
+    let name = text::normalize("name", name, TextKind::Line)?;
+
     if name.is_empty() {
-        app::bail!(Error::UserNameCannotBeEmpty);
+        bail!(Error::UserNameCannotBeEmpty);
     }
 
-    if name.len() > MAX_USER_NAME_LENGTH {
-        app::bail!(Error::UserNameTooLong {
-            max: MAX_USER_NAME_LENGTH,
-            got: name.len(),
+    let got = text::measure(&name, config.length_policy);
+
+    if got > config.max_user_name_length {
+        bail!(Error::UserNameTooLong {
+            max: config.max_user_name_length,
+            got,
         });
     }
 
-    Ok(())
+    Ok(name)
 }
 
-fn validate_skill(skill: &str) -> app::Result<()> {
-    if skill.len() > MAX_USER_SKILL_LENGTH {
-        app::bail!(Error::UserSkillTooLong {
+pub fn validate_skill(config: &AppConfig, skill: &str) -> app::Result<String> {
+    let skill = normalize_skill(skill)?;
+
+    if skill.is_empty() {
+        bail!(Error::UserSkillCannotBeEmpty);
+    }
+
+    let got = text::measure(&skill, config.length_policy);
+
+    if got > config.max_user_skill_length {
+        bail!(Error::UserSkillTooLong {
             skill,
-            max: MAX_USER_SKILL_LENGTH,
-            got: skill.len(),
+            max: config.max_user_skill_length,
+            got,
         });
     }
 
-    Ok(())
+    Ok(skill)
 }
 
-fn validate_link(link: &str) -> app::Result<()> {
-    if link.len() > MAX_USER_LINK_LENGTH {
-        app::bail!(Error::UserLinkTooLong {
+fn validate_link(config: &AppConfig, link: &str) -> app::Result<String> {
+    let link = canonical_link(link)?;
+
+    let got = text::measure(&link, config.length_policy);
+
+    if got > config.max_user_link_length {
+        bail!(Error::UserLinkTooLong {
             link,
-            max: MAX_USER_LINK_LENGTH,
-            got: link.len(),
+            max: config.max_user_link_length,
+            got,
         });
     }
 
-    Ok(())
+    Ok(link)
 }
 
 #[app::logic]
@@ -143,27 +227,32 @@ impl AppState {
     ) -> app::Result<UserId> {
         let user_id = self.current_user();
 
-        if self.users.contains(&user_id)? {
-            app::bail!(Error::UserAlreadyRegistered);
+        if self.users.contains(&user_id).map_err(storage)? {
+            bail!(Error::UserAlreadyRegistered(user_id));
         }
 
-        if let Some(name) = &name {
-            validate_user_name(name)?;
-        }
+        let name = name
+            .as_deref()
+            .map(|name| validate_user_name(&self.config, name))
+            .transpose()?;
 
         let skills = skills
-            .into_iter()
-            .map(|s| validate_skill(&s).map(|_| s))
-            .collect::<app::Result<_>>()?;
+            .iter()
+            .map(|s| validate_skill(&self.config, s))
+            .collect::<app::Result<BTreeSet<_>>>()?;
 
         let links = links
-            .into_iter()
-            .map(|s| validate_link(&s).map(|_| s))
+            .iter()
+            .map(|s| validate_link(&self.config, s))
             .collect::<app::Result<_>>()?;
 
+        for skill in &skills {
+            self.index_user_skill(user_id, skill.clone())?;
+        }
+
         let user = User {
             name,
-            skills,
+            skills: skills.into_iter().collect(),
             links,
 
             total_reward: 0,
@@ -171,12 +260,15 @@ impl AppState {
             bids: UnorderedSet::new(),
             assignments: UnorderedSet::new(),
             bounties: UnorderedSet::new(),
+            invitations: UnorderedSet::new(),
 
             messages: UnorderedSet::new(),
             remarks: UnorderedSet::new(),
+
+            edits: UnorderedSet::new(),
         };
 
-        let _ignored = self.users.insert(user_id, user)?;
+        let _ignored = self.users.insert(user_id, user).map_err(storage)?;
 
         Ok(user_id)
     }
@@ -193,7 +285,8 @@ pub struct UserDelta {
     pub links: Vec<DeltaOperation<String>>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[borsh(crate = "calimero_sdk::borsh")]
 #[serde(crate = "calimero_sdk::serde")]
 pub enum DeltaOperation<T> {
     Add(T),
@@ -203,53 +296,121 @@ pub enum DeltaOperation<T> {
 #[app::logic]
 impl AppState {
     pub fn update_user(&mut self, user_id: UserId, delta: UserDelta) -> app::Result<()> {
+        let actor = self.current_user();
+
+        if actor != user_id && !self.is_admin(&actor)? {
+            bail!(Error::UserEditForbidden {
+                actor,
+                user: user_id,
+            });
+        }
+
         let mut user = self.get_registered_user(&user_id)?;
 
+        let mut changes = Vec::new();
+
+        // the trail records values as they were stored, not as they were sent
         if let Some(op) = delta.name {
-            match op {
+            let applied = match op {
                 DeltaOperation::Add(name) => {
-                    validate_user_name(&name)?;
+                    let name = validate_user_name(&self.config, &name)?;
 
-                    user.name = Some(name)
+                    user.name = Some(name.clone());
+
+                    DeltaOperation::Add(name)
+                }
+                DeltaOperation::Remove(_) => {
+                    user.name = None;
+
+                    DeltaOperation::Remove(None)
                 }
-                DeltaOperation::Remove(_) => user.name = None,
-            }
+            };
+
+            changes.push(UserChange::Name(applied));
         }
+
         for op in delta.skills {
-            match op {
+            let applied = match op {
                 DeltaOperation::Add(skill) => {
-                    validate_skill(&skill)?;
+                    let skill = validate_skill(&self.config, &skill)?;
 
-                    let _ignored = user.skills.insert(skill)?;
+                    if user.skills.insert(skill.clone()).map_err(storage)? {
+                        self.index_user_skill(user_id, skill.clone())?;
+                    }
+
+                    DeltaOperation::Add(skill)
                 }
                 DeltaOperation::Remove(skill) => {
                     if let Some(skill) = skill {
-                        let _ignored = user.skills.remove(&skill)?;
+                        let skill = normalize_skill(&skill)?;
+
+                        if user.skills.remove(&skill).map_err(storage)? {
+                            self.unindex_user_skill(user_id, skill.clone())?;
+                        }
+
+                        DeltaOperation::Remove(Some(skill))
                     } else {
-                        user.skills.clear()?
+                        for skill in user.skills.iter().map_err(storage)? {
+                            self.unindex_user_skill(user_id, skill)?;
+                        }
+
+                        user.skills.clear().map_err(storage)?;
+
+                        DeltaOperation::Remove(None)
                     }
                 }
             };
+
+            changes.push(UserChange::Skill(applied));
         }
 
         for op in delta.links {
-            match op {
+            let applied = match op {
                 DeltaOperation::Add(link) => {
-                    validate_link(&link)?;
+                    let link = validate_link(&self.config, &link)?;
 
-                    let _ignored = user.links.insert(link)?;
+                    let _ignored = user.links.insert(link.clone()).map_err(storage)?;
+
+                    DeltaOperation::Add(link)
                 }
                 DeltaOperation::Remove(link) => {
                     if let Some(link) = link {
-                        let _ignored = user.links.remove(&link)?;
+                        let link = canonical_link(&link)?;
+
+                        if !user.links.remove(&link).map_err(storage)? {
+                            bail!(Error::UserLinkNotFound(link));
+                        }
+
+                        DeltaOperation::Remove(Some(link))
                     } else {
-                        user.links.clear()?
+                        user.links.clear().map_err(storage)?;
+
+                        DeltaOperation::Remove(None)
                     }
                 }
             };
+
+            changes.push(UserChange::Link(applied));
         }
 
-        let _ignored = self.users.insert(user_id, user)?;
+        if !changes.is_empty() {
+            let edit_id = self
+                .ids
+                .next(|id: &UserEditId| self.user_edits.contains(id))?;
+
+            let edit = UserEdit {
+                user: user_id,
+                actor,
+                timestamp: env::time_now(),
+                changes,
+            };
+
+            let _ignored = user.edits.insert(edit_id).map_err(storage)?;
+
+            let _ignored = self.user_edits.insert(edit_id, edit).map_err(storage)?;
+        }
+
+        let _ignored = self.users.insert(user_id, user).map_err(storage)?;
 
         Ok(())
     }
@@ -264,67 +425,302 @@ pub struct UserViewBrief {
     // pub rank: Option<u32>,
 }
 
+impl AppState {
+    pub fn internal_get_user_brief(&self, user_id: UserId) -> ItemResult<UserViewBrief, Error> {
+        let Some(user) = self.users.get(&user_id).map_err(storage)? else {
+            return Err(Error::UserNotRegistered(user_id).into());
+        };
+
+        Ok(UserViewBrief::new(user_id, user)?)
+    }
+}
+
+impl UserViewBrief {
+    pub fn new(id: UserId, user: User) -> Result<Self, InternalError> {
+        let skills = user.skills.iter().map_err(storage)?.take(5);
+        let skills = skills.map(|s| truncate_string(&s, 20).into()).collect();
+
+        Ok(Self {
+            id,
+            name: user.name,
+            skills,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "calimero_sdk::serde")]
 pub struct UserView {
     pub id: UserId,
     pub name: Option<String>,
     pub skills: Vec<String>,
-    pub links: Vec<String>,
+    pub links: Vec<LinkView>,
     pub total_reward: u128,
+    /// A preview of at most [`PROFILE_PREVIEW_LENGTH`] bids, page through
+    /// the rest with `list_user_bids`, likewise for the other lists.
     pub bids: Vec<BidId>,
-    pub assignments: Vec<BidId>,
+    pub assignments: Vec<AssignmentId>,
     pub bounties: Vec<BountyId>,
     pub messages: Vec<MessageId>,
+    pub totals: UserViewTotals,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct UserViewTotals {
+    pub skills: usize,
+    pub links: usize,
+    pub bids: usize,
+    pub assignments: usize,
+    pub bounties: usize,
+    pub messages: usize,
 }
 
 #[app::logic]
 impl AppState {
     pub fn get_user_brief(&self, user_id: UserId) -> app::Result<Option<UserViewBrief>> {
-        let user = self.users.get(&user_id)?;
+        let user = self.users.get(&user_id).map_err(storage)?;
 
         let Some(user) = user else {
             return Ok(None);
         };
 
-        let skills = user.skills.iter()?.take(5);
-        let skills = skills.map(|s| truncate_string(&s, 20).into()).collect();
+        Ok(Some(UserViewBrief::new(user_id, user)?))
+    }
 
-        Ok(Some(UserViewBrief {
-            id: user_id,
-            name: user.name,
-            skills,
-        }))
+    pub fn get_users_brief(
+        &self,
+        user_ids: Vec<UserId>,
+    ) -> app::Result<Vec<ItemResult<UserViewBrief, Error>>> {
+        let briefs = user_ids
+            .into_iter()
+            .map(|user_id| self.internal_get_user_brief(user_id));
+
+        Ok(briefs.collect())
     }
 
     pub fn get_user(&self, user_id: UserId) -> app::Result<Option<UserView>> {
-        let user = self.users.get(&user_id)?;
+        let user = self.users.get(&user_id).map_err(storage)?;
 
         let Some(user) = user else {
             return Ok(None);
         };
 
-        let links = user.links.iter()?;
-        let skills = user.skills.iter()?;
-        let bids = user.bids.iter()?;
-        let assignments = user.assignments.iter()?;
-        let bounties = user.bounties.iter()?;
-        let messages = user.messages.iter()?;
+        let totals = UserViewTotals {
+            skills: user.skills.len().map_err(storage)?,
+            links: user.links.len().map_err(storage)?,
+            bids: user.bids.len().map_err(storage)?,
+            assignments: user.assignments.len().map_err(storage)?,
+            bounties: user.bounties.len().map_err(storage)?,
+            messages: user.messages.len().map_err(storage)?,
+        };
+
+        let links = user.links.iter().map_err(storage)?;
+        let skills = user.skills.iter().map_err(storage)?;
+        let bids = user.bids.iter().map_err(storage)?;
+        let assignments = user.assignments.iter().map_err(storage)?;
+        let bounties = user.bounties.iter().map_err(storage)?;
+        let messages = user.messages.iter().map_err(storage)?;
 
         Ok(Some(UserView {
             id: user_id,
             name: user.name,
-            skills: skills.take(3).collect(),
-            links: links.take(3).collect(),
+            skills: skills.collect(),
+            links: links.map(LinkView::from).collect(),
             total_reward: user.total_reward,
-            bids: bids.take(3).collect(),
-            assignments: assignments.take(3).collect(),
-            bounties: bounties.take(3).collect(),
-            messages: messages.take(3).collect(),
+            bids: bids.take(PROFILE_PREVIEW_LENGTH).collect(),
+            assignments: assignments.take(PROFILE_PREVIEW_LENGTH).collect(),
+            bounties: bounties.take(PROFILE_PREVIEW_LENGTH).collect(),
+            messages: messages.take(PROFILE_PREVIEW_LENGTH).collect(),
+            totals,
         }))
     }
 }
 
+#[app::logic]
+impl AppState {
+    pub fn list_user_bids(
+        &self,
+        user_id: UserId,
+        status: Option<BidStatusFilter>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<BidId>> {
+        let user = self.get_registered_user(&user_id)?;
+
+        let mut bids = user
+            .bids
+            .iter()
+            .map_err(storage)?
+            .map(|bid_id| {
+                let Some(bid) = self.bids.get(&bid_id).map_err(storage)? else {
+                    return Ok(None);
+                };
+
+                if let Some(status) = &status {
+                    if !status.against(&bid.status) {
+                        return Ok(None);
+                    }
+                }
+
+                Ok(Some((bid_id, self.internal_bid_proposed_at(&bid)?)))
+            })
+            .filter_map(|e| e.transpose())
+            .collect::<app::Result<Vec<_>>>()?;
+
+        bids.sort_by_key(|(id, proposed_at)| (Reverse(*proposed_at), *id));
+
+        Ok(paginate(bids.into_iter().map(|(id, _)| id), offset, length))
+    }
+
+    pub fn list_user_assignments(
+        &self,
+        user_id: UserId,
+        status: Option<AssignmentStatusFilter>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<AssignmentId>> {
+        let user = self.get_registered_user(&user_id)?;
+
+        let mut assignments = user
+            .assignments
+            .iter()
+            .map_err(storage)?
+            .map(|assignment_id| {
+                let Some(assignment) = self.assignments.get(&assignment_id).map_err(storage)?
+                else {
+                    return Ok(None);
+                };
+
+                if let Some(status) = &status {
+                    if !status.against(&assignment.status) {
+                        return Ok(None);
+                    }
+                }
+
+                Ok(Some((assignment_id, assignment.assigned_at)))
+            })
+            .filter_map(|e| e.transpose())
+            .collect::<app::Result<Vec<_>>>()?;
+
+        assignments.sort_by_key(|(id, assigned_at)| (Reverse(*assigned_at), *id));
+
+        Ok(paginate(
+            assignments.into_iter().map(|(id, _)| id),
+            offset,
+            length,
+        ))
+    }
+
+    pub fn list_user_bounties(
+        &self,
+        user_id: UserId,
+        status: Option<BountyStatusFilter>,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<BountyId>> {
+        let user = self.get_registered_user(&user_id)?;
+
+        let mut bounties = user
+            .bounties
+            .iter()
+            .map_err(storage)?
+            .map(|bounty_id| {
+                let Some(bounty) = self.bounties.get(&bounty_id).map_err(storage)? else {
+                    return Ok(None);
+                };
+
+                if let Some(status) = &status {
+                    if !status.against(&bounty.status) {
+                        return Ok(None);
+                    }
+                }
+
+                Ok(Some((bounty_id, bounty.proposed_at)))
+            })
+            .filter_map(|e| e.transpose())
+            .collect::<app::Result<Vec<_>>>()?;
+
+        bounties.sort_by_key(|(id, proposed_at)| (Reverse(*proposed_at), *id));
+
+        Ok(paginate(
+            bounties.into_iter().map(|(id, _)| id),
+            offset,
+            length,
+        ))
+    }
+
+    pub fn list_user_messages(
+        &self,
+        user_id: UserId,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> app::Result<Vec<MessageId>> {
+        let user = self.get_registered_user(&user_id)?;
+
+        let mut messages = user
+            .messages
+            .iter()
+            .map_err(storage)?
+            .map(|message_id| {
+                let Some(message) = self.messages.get(&message_id).map_err(storage)? else {
+                    return Ok(None);
+                };
+
+                Ok(Some((message_id, message.timestamp)))
+            })
+            .filter_map(|e| e.transpose())
+            .collect::<app::Result<Vec<_>>>()?;
+
+        messages.sort_by_key(|(id, timestamp)| (Reverse(*timestamp), *id));
+
+        Ok(paginate(
+            messages.into_iter().map(|(id, _)| id),
+            offset,
+            length,
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "calimero_sdk::serde")]
+pub struct UserEditView {
+    pub id: UserEditId,
+    pub actor: UserId,
+    pub timestamp: u64,
+    pub changes: Vec<UserChange>,
+}
+
+#[app::logic]
+impl AppState {
+    pub fn get_user_edits(&self, user_id: UserId) -> app::Result<Vec<UserEditView>> {
+        let user = self.get_registered_user(&user_id)?;
+
+        let mut edits = user
+            .edits
+            .iter()
+            .map_err(storage)?
+            .map(|edit_id| {
+                let Some(edit) = self.user_edits.get(&edit_id).map_err(storage)? else {
+                    return Ok(None);
+                };
+
+                Ok(Some(UserEditView {
+                    id: edit_id,
+                    actor: edit.actor,
+                    timestamp: edit.timestamp,
+                    changes: edit.changes,
+                }))
+            })
+            .filter_map(|e| e.transpose())
+            .collect::<app::Result<Vec<_>>>()?;
+
+        edits.sort_by_key(|edit| edit.timestamp);
+
+        Ok(edits)
+    }
+}
+
 #[app::logic]
 impl AppState {
     // pub fn submit_remark(
@@ -333,17 +729,62 @@ impl AppState {
     //     review: f32,
     //     message_id: MessageId,
     // ) -> app::Result<()> {
-    //     let mut user = ensure_registered!(self.users.get(&user_id)?);
+    //     let mut user = ensure_registered!(self.users.get(&user_id).map_err(storage)?);
 
     //     let remark = UserRemarks {
     //         review,
     //         message: message_id,
     //     };
 
-    //     let _ignored = user.remarks.insert(remark)?;
+    //     let _ignored = user.remarks.insert(remark).map_err(storage)?;
 
-    //     self.users.insert(user_id, user)?;
+    //     self.users.insert(user_id, user).map_err(storage)?;
 
     //     Ok(())
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{user, Harness};
+
+    fn add_skill(skill: &str) -> UserDelta {
+        UserDelta {
+            name: None,
+            skills: vec![DeltaOperation::Add(skill.to_owned())],
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn update_user_checks_the_calling_executor() {
+        let (alice, bob) = (user(2), user(3));
+        let mut harness = Harness::new(user(1));
+
+        for (user_id, name) in [(alice, "alice"), (bob, "bob")] {
+            let state = harness.as_user(user_id);
+
+            assert_eq!(state.current_user(), user_id);
+
+            let _user_id = state
+                .register(Some(name.to_owned()), BTreeSet::new(), BTreeSet::new())
+                .unwrap();
+        }
+
+        assert!(harness
+            .as_user(bob)
+            .update_user(alice, add_skill("rust"))
+            .is_err());
+
+        harness
+            .as_user(alice)
+            .update_user(alice, add_skill("rust"))
+            .unwrap();
+
+        let edits = harness.state().get_user_edits(alice).unwrap();
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].actor, alice);
+    }
+}
@@ -1,7 +1,31 @@
 use std::borrow::Cow;
 use std::convert::Infallible;
+use std::fmt;
 
 use calimero_sdk::app;
+use calimero_sdk::serde::Serialize;
+use thiserror::Error;
+
+use crate::error::{bail, storage, ErrorCode};
+
+#[derive(Debug, Error, Serialize)]
+#[serde(crate = "calimero_sdk::serde")]
+#[serde(tag = "kind", content = "data")]
+pub enum Error {
+    #[error("unable to determine unique value for `{type_name}` after {attempts} attempts")]
+    UniqueValueExhausted {
+        type_name: &'static str,
+        attempts: usize,
+    },
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> u16 {
+        match self {
+            Error::UniqueValueExhausted { .. } => 9002,
+        }
+    }
+}
 
 pub trait IntoResult<T> {
     type Error;
@@ -28,26 +52,27 @@ impl<T, E> IntoResult<T> for Result<T, E> {
 pub fn unique<T, R>(factory: impl Fn() -> T, test: impl Fn(&T) -> R) -> app::Result<T>
 where
     R: IntoResult<bool>,
-    R::Error: core::error::Error,
+    R::Error: fmt::Display,
 {
     for _ in 0..10 {
         let value = factory();
-        if !test(&value).into_result()? {
+        if !test(&value).into_result().map_err(storage)? {
             return Ok(value);
         }
     }
 
-    app::bail!(
-        "unable to determine unique value for `{}` after 10 attempts",
-        std::any::type_name::<T>()
-    );
+    bail!(Error::UniqueValueExhausted {
+        type_name: std::any::type_name::<T>(),
+        attempts: 10,
+    });
 }
 
 pub fn truncate_string(s: &str, max_width: usize) -> Cow<'_, str> {
-    if s.len() <= max_width {
+    if s.chars().count() <= max_width {
         return s.into();
     }
-    s.chars().take(max_width - 1).chain(Some('…')).collect()
+    let kept = max_width.saturating_sub(1);
+    s.chars().take(kept).chain(Some('…')).collect()
 }
 
 pub mod borsh_char {
@@ -0,0 +1,36 @@
+use calimero_sdk::app;
+
+use crate::bounty::BountyId;
+use crate::private::PrivateRootState;
+use crate::AppState;
+
+#[app::logic]
+impl AppState {
+    pub fn watch_bounty(&mut self, bounty_id: BountyId) -> app::Result<()> {
+        let _bounty = self.internal_get_bounty(bounty_id)?;
+
+        let mut root = PrivateRootState::load()?;
+
+        let mut state = root.as_mut();
+
+        let _ignored = state.watchlist.insert(bounty_id);
+
+        state.commit()
+    }
+
+    pub fn unwatch_bounty(&mut self, bounty_id: BountyId) -> app::Result<()> {
+        let mut root = PrivateRootState::load()?;
+
+        let mut state = root.as_mut();
+
+        let _ignored = state.watchlist.remove(&bounty_id);
+
+        state.commit()
+    }
+
+    pub fn list_watched_bounties(&self) -> app::Result<Vec<BountyId>> {
+        let root = PrivateRootState::load()?;
+
+        Ok(root.watchlist.iter().copied().collect())
+    }
+}